@@ -1,6 +1,46 @@
 /// Maximum fee in basis points (10% = 1000)
 pub const MAX_FEE_LIMIT: u16 = 1000;
 
+/// Basis points denominator (100% = 10000)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Seconds in a day, for daily deposit limits
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Seconds in a week, for weekly deposit limits
+pub const SECONDS_PER_WEEK: i64 = 7 * SECONDS_PER_DAY;
+
+/// How long a raised or removed deposit limit waits before it takes effect
+pub const LIMIT_COOLING_OFF_SECONDS: i64 = 7 * SECONDS_PER_DAY;
+
+/// Longest self-exclusion a user can set in one call (5 years)
+pub const MAX_SELF_EXCLUSION_SECONDS: i64 = 5 * 365 * SECONDS_PER_DAY;
+
+/// Highest fixed-odds payout multiplier (100x)
+pub const MAX_FIXED_ODDS_BPS: u32 = 1_000_000;
+
+/// Default delay between queueing and executing a config change (2 days)
+pub const DEFAULT_TIMELOCK_DELAY_SECONDS: i64 = 2 * 24 * 60 * 60;
+
+/// Shortest timelock delay a config change can set (1 hour)
+pub const MIN_TIMELOCK_DELAY_SECONDS: i64 = 60 * 60;
+
+/// Longest timelock delay a config change can set (30 days)
+pub const MAX_TIMELOCK_DELAY_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// Default maximum age of a Pyth price update accepted at resolution
+pub const DEFAULT_MAX_PRICE_AGE_SECONDS: u64 = 30;
+
+/// Largest maximum price age the oracle policy can set (1 hour)
+pub const MAX_PRICE_AGE_LIMIT_SECONDS: u64 = 60 * 60;
+
+/// Smallest order the book accepts, in whole YES shares, so dust cannot fill it
+pub const MIN_ORDER_SHARES: u64 = 1;
+
+/// Smallest order value the book accepts, in whole collateral units, so the book's capped
+/// trader slots cannot be filled with orders at a near-zero price
+pub const MIN_ORDER_VALUE: u64 = 1;
+
 /// Maximum question length
 pub const MAX_QUESTION_LENGTH: usize = 200;
 
@@ -13,8 +53,17 @@ pub const SOL_USD_FEED_ID: [u8; 32] = [
     0x0f, 0x4c, 0xfa, 0xc8, 0xc2, 0x80, 0xb5, 0x6d,
 ];
 
-/// Allowed creators for markets (whitelist)
-pub const ALLOWED_CREATORS: [&str; 2] = [
-    "5YXbYQCnBSTX3fmYbuGNSbY3J24v5KRZ11aWh856WWc3",
-    "455q3UD1KkfMP7zWrd2XcYoZW8LaVoiU969cmusengZ9",
-];
+/// How long after the resolution time committed predictions can be revealed (14 days)
+pub const REVEAL_WINDOW_SECONDS: i64 = 14 * 24 * 60 * 60;
+
+/// How long after resolution it can be disputed, with every payout under it held back
+/// (1 day)
+pub const DISPUTE_WINDOW_SECONDS: i64 = 24 * 60 * 60;
+
+/// How long winners have to claim after the reveal deadline (or after cancellation)
+/// before the remaining vault balance can be swept (90 days)
+pub const CLAIM_WINDOW_SECONDS: i64 = 90 * 24 * 60 * 60;
+
+/// Delay after a market's resolution time before its creator bond can be released,
+/// giving the admin a window to slash bonds of malformed or misleading markets
+pub const BOND_RELEASE_DELAY_SECONDS: i64 = 3 * 24 * 60 * 60;
@@ -64,4 +64,146 @@ pub enum PredictionMarketError {
     PythPriceTooOld,
     #[msg("Pyth price feed ID mismatch")]
     PythFeedIdMismatch,
+    #[msg("Creator has reached its open market limit")]
+    CreatorMarketLimitReached,
+    #[msg("Creator is not allowed to create markets on this price feed")]
+    FeedNotAllowed,
+    #[msg("Too many allowed feeds for creator")]
+    TooManyCreatorFeeds,
+    #[msg("Permissionless market creation is disabled")]
+    PermissionlessCreationDisabled,
+    #[msg("Creator bond is not locked")]
+    BondNotLocked,
+    #[msg("Creator bond cannot be released yet")]
+    BondReleaseTooEarly,
+    #[msg("No admin transfer is pending")]
+    NoPendingAdmin,
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+    #[msg("Invalid new admin")]
+    InvalidNewAdmin,
+    #[msg("Signer does not hold the required role")]
+    MissingRole,
+    #[msg("Config change timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Invalid timelock delay")]
+    InvalidTimelockDelay,
+    #[msg("Market is paused")]
+    MarketPaused,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    #[msg("Account is already at the current layout version")]
+    AlreadyMigrated,
+    #[msg("Position has not been claimed or forfeited")]
+    PositionNotSettled,
+    #[msg("Market still has open positions")]
+    OpenPositionsRemain,
+    #[msg("Creator bond is still locked")]
+    BondStillLocked,
+    #[msg("Claim window has expired")]
+    ClaimWindowExpired,
+    #[msg("Claim window has not expired yet")]
+    ClaimWindowNotExpired,
+    #[msg("Market vault has already been swept")]
+    MarketSwept,
+    #[msg("Market accounting mismatch: outflows exceed deposits")]
+    AccountingMismatch,
+    #[msg("Market vault balance is below outstanding liabilities")]
+    VaultInsolvent,
+    #[msg("Exit amount exceeds position")]
+    InsufficientPosition,
+    #[msg("Post-bet payout multiplier is below the requested minimum")]
+    SlippageExceeded,
+    #[msg("Token account does not hold this market's outcome shares")]
+    InvalidShareMint,
+    #[msg("Amount exceeds outstanding complete sets")]
+    InsufficientCompleteSets,
+    #[msg("Outcome shares are still outstanding")]
+    SharesOutstanding,
+    #[msg("Liquidity parameter must be greater than zero")]
+    InvalidLiquidity,
+    #[msg("Instruction not supported for this market type")]
+    WrongMarketType,
+    #[msg("Market already has bets or shares")]
+    MarketNotEmpty,
+    #[msg("Market maker pool account required for this market type")]
+    MissingAmmPool,
+    #[msg("Market maker collateral does not cover outstanding shares")]
+    AmmInsolvent,
+    #[msg("Trade cost or proceeds outside the requested limit")]
+    TradeLimitExceeded,
+    #[msg("Creator subsidy already withdrawn")]
+    SubsidyAlreadyWithdrawn,
+    #[msg("Not enough liquidity in the pool")]
+    InsufficientLiquidity,
+    #[msg("Order book is full")]
+    OrderBookFull,
+    #[msg("Order price must be between 1 and 9999 basis points")]
+    InvalidOrderPrice,
+    #[msg("Order is too small to trade")]
+    OrderTooSmall,
+    #[msg("Order not found")]
+    OrderNotFound,
+    #[msg("Only the order owner can cancel it")]
+    NotOrderOwner,
+    #[msg("Order book still holds resting orders or unsettled balances")]
+    OrderBookNotSettled,
+    #[msg("Fixed odds must pay more than the stake and at most the protocol maximum")]
+    InvalidOdds,
+    #[msg("House pool accounts required for fixed-odds markets")]
+    MissingHousePool,
+    #[msg("Commit would exceed the market's maximum house liability")]
+    MaxLiabilityExceeded,
+    #[msg("House pool has too little unreserved liquidity")]
+    InsufficientHouseLiquidity,
+    #[msg("Fixed-odds liability can only be released once the market has settled")]
+    LiabilityNotReleasable,
+    #[msg("Amount is below the market's minimum bet")]
+    BetBelowMinimum,
+    #[msg("Amount would exceed the per-user stake limit")]
+    UserStakeLimitExceeded,
+    #[msg("Amount would exceed the market's maximum total pool")]
+    PoolLimitExceeded,
+    #[msg("Minimum bet exceeds a maximum limit")]
+    InvalidStakeLimits,
+    #[msg("User is self-excluded")]
+    SelfExcluded,
+    #[msg("Deposit would exceed the user's deposit limit")]
+    DepositLimitExceeded,
+    #[msg("Self-exclusion duration must be positive and within the maximum")]
+    InvalidExclusionPeriod,
+    #[msg("Wallet is not on the market's allowlist")]
+    NotAllowlisted,
+    #[msg("Market requires a valid credential of a higher tier")]
+    CredentialRequired,
+    #[msg("No attester is registered")]
+    NoAttester,
+    #[msg("Missing or malformed attester signature instruction")]
+    InvalidAttestation,
+    #[msg("Credential is expired or not newer than the recorded one")]
+    StaleCredential,
+    #[msg("Only the attester can revoke credentials")]
+    NotAttester,
+    #[msg("Instruction is not available in this market's mode")]
+    WrongMarketMode,
+    #[msg("Market resolution is under dispute")]
+    MarketDisputed,
+    #[msg("Instruction not allowed in the market's current state")]
+    InvalidMarketState,
+    #[msg("Illegal market state transition")]
+    IllegalStateTransition,
+    #[msg("Resolution cannot be overturned after payouts, reveals or forfeits")]
+    DisputeAfterSettlement,
+    #[msg("No creation fee is held in escrow")]
+    NoEscrowedFee,
+    #[msg("Maximum price age must be positive and at most the protocol limit")]
+    InvalidMaxPriceAge,
+    #[msg("The creator's allowlist entry is needed to free its open market slot")]
+    CreatorEntryRequired,
+    #[msg("Payouts wait until the dispute window after resolution has passed")]
+    DisputeWindowOpen,
+    #[msg("Dispute window has closed or the market was already disputed")]
+    DisputeNotAllowed,
+    #[msg("Evicted order's owner token account is missing or belongs to someone else")]
+    EvictedOwnerAccountMismatch,
 }
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{ConfigChange, MarketState, Outcome};
+
+/// Emitted when a configuration change is queued behind the timelock
+#[event]
+pub struct ConfigChangeQueued {
+    pub change_id: u64,
+    pub change: ConfigChange,
+    pub proposer: Pubkey,
+    pub eta: i64,
+}
+
+/// Emitted when a queued configuration change is applied
+#[event]
+pub struct ConfigChangeExecuted {
+    pub change_id: u64,
+    pub change: ConfigChange,
+}
+
+/// Emitted when a queued configuration change is cancelled
+#[event]
+pub struct ConfigChangeCancelled {
+    pub change_id: u64,
+    pub change: ConfigChange,
+}
+
+/// Emitted when a market passes a solvency audit
+#[event]
+pub struct MarketAudited {
+    pub market_id: u64,
+    pub vault_balance: u64,
+    pub liabilities: u64,
+}
+
+/// Emitted on every LMSR trade, with the YES probability after the trade
+#[event]
+pub struct LmsrTrade {
+    pub market_id: u64,
+    pub trader: Pubkey,
+    pub outcome: Outcome,
+    pub is_buy: bool,
+    pub shares: u64,
+    pub amount: u64,
+    pub yes_price_bps: u64,
+}
+
+/// Emitted on every constant-product pool trade, with the YES price after the trade
+#[event]
+pub struct CpmmTrade {
+    pub market_id: u64,
+    pub trader: Pubkey,
+    pub outcome: Outcome,
+    pub is_buy: bool,
+    pub shares: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub yes_price_bps: u64,
+}
+
+/// Emitted for every match between a resting bid and ask, at the older order's price
+#[event]
+pub struct OrderFilled {
+    pub market_id: u64,
+    pub bid_order_id: u64,
+    pub ask_order_id: u64,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub price_bps: u16,
+    pub quantity: u64,
+    pub bid_remaining: u64,
+    pub ask_remaining: u64,
+}
+
+/// Emitted on every stored market state transition
+#[event]
+pub struct MarketStateChanged {
+    pub market_id: u64,
+    pub from: MarketState,
+    pub to: MarketState,
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::error::PredictionMarketError;
+use crate::state::Config;
+
+/// Admin-signed handover actions: propose, cancel and renounce
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    #[account(
+        constraint = admin.key() == config.admin @ PredictionMarketError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        constraint = config.pending_admin == Some(new_admin.key()) @ PredictionMarketError::NotPendingAdmin
+    )]
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Propose a new admin. Control only moves once the new admin accepts.
+pub fn propose_admin_handler(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+    require!(
+        new_admin != Pubkey::default() && new_admin != ctx.accounts.config.admin,
+        PredictionMarketError::InvalidNewAdmin
+    );
+
+    ctx.accounts.config.pending_admin = Some(new_admin);
+
+    msg!("Admin transfer proposed");
+    msg!("Current Admin: {}", ctx.accounts.config.admin);
+    msg!("Pending Admin: {}", new_admin);
+
+    Ok(())
+}
+
+pub fn accept_admin_handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+    config.admin = ctx.accounts.new_admin.key();
+    config.pending_admin = None;
+
+    msg!("Admin transfer accepted");
+    msg!("Previous Admin: {}", previous_admin);
+    msg!("New Admin: {}", config.admin);
+
+    Ok(())
+}
+
+pub fn cancel_admin_transfer_handler(ctx: Context<TransferAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let pending_admin = config
+        .pending_admin
+        .take()
+        .ok_or(PredictionMarketError::NoPendingAdmin)?;
+
+    msg!("Admin transfer cancelled");
+    msg!("Cancelled Pending Admin: {}", pending_admin);
+
+    Ok(())
+}
+
+/// Permanently give up admin control. Admin-only instructions become unusable afterwards.
+pub fn renounce_admin_handler(ctx: Context<TransferAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+    config.admin = Pubkey::default();
+    config.pending_admin = None;
+
+    msg!("Admin renounced");
+    msg!("Previous Admin: {}", previous_admin);
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::error::PredictionMarketError;
+use crate::events::MarketAudited;
+use crate::state::Market;
+
+/// Check that a market's vault holds at least what the market's accounting says it owes.
+/// Permissionless and read-only; fails with `VaultInsolvent` if the invariant is broken.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct AuditMarket<'info> {
+    #[account(
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+}
+
+pub fn handler(ctx: Context<AuditMarket>, market_id: u64) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let vault_balance = ctx.accounts.market_vault.amount;
+    let liabilities = market.outstanding_liabilities()?;
+
+    msg!("Market audit");
+    msg!("Market ID: {}", market_id);
+    msg!("Deposits: {}", market.total_deposits()?);
+    msg!("  YES Pool: {}", market.yes_pool);
+    msg!("  NO Pool: {}", market.no_pool);
+    msg!("  Exit Fees: {}", market.exit_fees);
+    msg!("  Complete Sets: {}", market.complete_sets);
+    msg!("  AMM Collateral: {}", market.amm_collateral);
+    msg!("  Committed: {}", market.total_committed);
+    msg!("  Revealed YES: {}", market.total_revealed_yes);
+    msg!("  Revealed NO: {}", market.total_revealed_no);
+    msg!("Paid Out: {}", market.total_paid_out);
+    msg!("Forfeited: {}", market.total_forfeited);
+    msg!("Fees: {}", market.total_fees);
+    msg!("Liabilities: {}", liabilities);
+    msg!("Vault Balance: {}", vault_balance);
+
+    require!(
+        vault_balance >= liabilities,
+        PredictionMarketError::VaultInsolvent
+    );
+
+    emit!(MarketAudited {
+        market_id,
+        vault_balance,
+        liabilities,
+    });
+
+    Ok(())
+}
@@ -1,11 +1,96 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
 
 use crate::error::PredictionMarketError;
-use crate::state::{Config, Market, MarketState};
+use crate::lifecycle::{require_phase, transition};
+use crate::state::{
+    AllowedCreator, CancelReason, Config, LmsrPool, Market, MarketState, MarketType, Role,
+};
 
 #[derive(Accounts)]
 #[instruction(market_id: u64)]
 pub struct CancelMarket<'info> {
+    #[account(
+        constraint = config.has_role(Role::Canceller, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketAlreadyFinalized
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Creator's allowlist entry (required if the market holds an open market slot)
+    #[account(
+        mut,
+        seeds = [AllowedCreator::SEED, market.creator.as_ref()],
+        bump = allowed_creator.bump
+    )]
+    pub allowed_creator: Option<Account<'info, AllowedCreator>>,
+}
+
+/// Withdraw a market nobody has entered yet (creator only). Refunds the escrowed
+/// creation fee; the creator bond follows the usual release rules.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct CreatorCancelMarket<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketAlreadyFinalized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::BOND_VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bond_vault_bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    /// Creator's token account (receives the creation fee)
+    #[account(
+        mut,
+        token::mint = bond_vault.mint,
+        token::authority = creator
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    /// LMSR pool, required for LMSR markets
+    #[account(
+        seeds = [LmsrPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump = lmsr_pool.bump
+    )]
+    pub lmsr_pool: Option<Account<'info, LmsrPool>>,
+
+    /// Creator's allowlist entry (required if the market holds an open market slot)
+    #[account(
+        mut,
+        seeds = [AllowedCreator::SEED, market.creator.as_ref()],
+        bump = allowed_creator.bump
+    )]
+    pub allowed_creator: Option<Account<'info, AllowedCreator>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Cancel an unresolved market at any time, recording a reason code (admin only)
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct EmergencyCancelMarket<'info> {
     #[account(
         constraint = admin.key() == config.admin @ PredictionMarketError::InvalidAdmin
     )]
@@ -24,23 +109,111 @@ pub struct CancelMarket<'info> {
         constraint = market.state == MarketState::Active @ PredictionMarketError::MarketAlreadyFinalized
     )]
     pub market: Account<'info, Market>,
+
+    /// Creator's allowlist entry (required if the market holds an open market slot)
+    #[account(
+        mut,
+        seeds = [AllowedCreator::SEED, market.creator.as_ref()],
+        bump = allowed_creator.bump
+    )]
+    pub allowed_creator: Option<Account<'info, AllowedCreator>>,
 }
 
-pub fn handler(ctx: Context<CancelMarket>, _market_id: u64) -> Result<()> {
+pub fn cancel_market_handler(ctx: Context<CancelMarket>, _market_id: u64) -> Result<()> {
     let market = &mut ctx.accounts.market;
     let clock = Clock::get()?;
 
     // Validations
-    require!(
-        clock.unix_timestamp >= market.resolution_time,
-        PredictionMarketError::MarketNotExpired
-    );
+    require_phase(market, clock.unix_timestamp, &[MarketState::BettingClosed])?;
 
     // Cancel market
-    market.state = MarketState::Cancelled;
+    transition(market, MarketState::Cancelled, clock.unix_timestamp)?;
+    market.cancel_reason = CancelReason::Manual;
+
+    // Free up the creator's open market slot
+    market.release_creator_slot(ctx.accounts.allowed_creator.as_deref_mut())?;
 
     msg!("Market cancelled");
     msg!("Market ID: {}", market.id);
+    msg!("Claim Expiry: {}", market.claim_expiry);
+    msg!("YES Pool: {}", market.yes_pool);
+    msg!("NO Pool: {}", market.no_pool);
+
+    Ok(())
+}
+
+pub fn creator_cancel_handler(ctx: Context<CreatorCancelMarket>, market_id: u64) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+
+    // Only markets nobody has bet, committed, traded or provided liquidity in. The
+    // creator's own LMSR subsidy does not count; it is withdrawn after the cancellation.
+    let subsidy = match market.market_type {
+        MarketType::PariMutuel | MarketType::Cpmm | MarketType::FixedOdds => 0,
+        MarketType::Lmsr => ctx
+            .accounts
+            .lmsr_pool
+            .as_ref()
+            .ok_or(PredictionMarketError::MissingAmmPool)?
+            .unwithdrawn_subsidy(),
+    };
+    require!(
+        market.is_untouched(subsidy)?,
+        PredictionMarketError::MarketNotEmpty
+    );
+
+    // Refund the escrowed creation fee
+    let refund = market.fee_escrow;
+    if refund > 0 {
+        let market_id_bytes = market_id.to_le_bytes();
+        let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bond_vault.to_account_info(),
+            to: ctx.accounts.creator_token_account.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, refund)?;
+    }
+
+    let market = &mut ctx.accounts.market;
+    market.fee_escrow = 0;
+    transition(market, MarketState::Cancelled, clock.unix_timestamp)?;
+    market.cancel_reason = CancelReason::Creator;
+
+    // Free up the creator's open market slot
+    market.release_creator_slot(ctx.accounts.allowed_creator.as_deref_mut())?;
+
+    msg!("Market withdrawn by creator");
+    msg!("Market ID: {}", market_id);
+    msg!("Fee Refunded: {}", refund);
+    msg!("Claim Expiry: {}", market.claim_expiry);
+
+    Ok(())
+}
+
+pub fn emergency_cancel_handler(
+    ctx: Context<EmergencyCancelMarket>,
+    market_id: u64,
+    reason_code: u16,
+) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let clock = Clock::get()?;
+
+    transition(market, MarketState::Cancelled, clock.unix_timestamp)?;
+    market.cancel_reason = CancelReason::Emergency;
+    market.cancel_code = reason_code;
+
+    // Free up the creator's open market slot
+    market.release_creator_slot(ctx.accounts.allowed_creator.as_deref_mut())?;
+
+    msg!("Market emergency cancelled");
+    msg!("Market ID: {}", market_id);
+    msg!("Reason Code: {}", reason_code);
+    msg!("Claim Expiry: {}", market.claim_expiry);
     msg!("YES Pool: {}", market.yes_pool);
     msg!("NO Pool: {}", market.no_pool);
 
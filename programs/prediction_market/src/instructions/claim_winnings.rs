@@ -2,7 +2,8 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
 
 use crate::error::PredictionMarketError;
-use crate::state::{Market, MarketState, Outcome, UserPosition};
+use crate::lifecycle::{require_dispute_window_passed, require_phase};
+use crate::state::{Config, Market, MarketState, Outcome, UserPosition};
 
 #[derive(Accounts)]
 #[instruction(market_id: u64)]
@@ -11,9 +12,18 @@ pub struct ClaimWinnings<'info> {
     pub user: Signer<'info>,
 
     #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
         seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
         bump = market.bump,
-        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused
     )]
     pub market: Account<'info, Market>,
 
@@ -47,54 +57,36 @@ pub fn handler(ctx: Context<ClaimWinnings>, market_id: u64) -> Result<()> {
     let market = &ctx.accounts.market;
     let position = &ctx.accounts.user_position;
 
-    // Check user has a position
+    // Check the claim window is still open
+    let clock = Clock::get()?;
+    require_phase(
+        market,
+        clock.unix_timestamp,
+        &[MarketState::Resolved, MarketState::Settled, MarketState::Cancelled],
+    )?;
+    require_dispute_window_passed(market, clock.unix_timestamp)?;
+
+    let (bets, refundable_commitment) = market.claimable_deposits(position)?;
     require!(
-        position.yes_bet > 0 || position.no_bet > 0,
+        bets > 0 || refundable_commitment > 0,
         PredictionMarketError::NoPosition
     );
 
     // Calculate payout
-    let payout: u64;
-
-    if market.state == MarketState::Resolved {
-        if market.winning_outcome == Outcome::Yes && position.yes_bet > 0 {
-            // User bet on YES and YES won
-            let winning_pool = market.yes_pool;
-            let losing_pool = market.no_pool;
-            // payout = user_bet + (user_bet * losing_pool) / winning_pool
-            let share = (position.yes_bet as u128)
-                .checked_mul(losing_pool as u128)
-                .ok_or(PredictionMarketError::Overflow)?
-                .checked_div(winning_pool as u128)
-                .ok_or(PredictionMarketError::Overflow)?;
-            payout = position
-                .yes_bet
-                .checked_add(share as u64)
-                .ok_or(PredictionMarketError::Overflow)?;
-        } else if market.winning_outcome == Outcome::No && position.no_bet > 0 {
-            // User bet on NO and NO won
-            let winning_pool = market.no_pool;
-            let losing_pool = market.yes_pool;
-            let share = (position.no_bet as u128)
-                .checked_mul(losing_pool as u128)
-                .ok_or(PredictionMarketError::Overflow)?
-                .checked_div(winning_pool as u128)
-                .ok_or(PredictionMarketError::Overflow)?;
-            payout = position
-                .no_bet
-                .checked_add(share as u64)
-                .ok_or(PredictionMarketError::Overflow)?;
-        } else {
-            // User bet on losing side
-            payout = 0;
-        }
+    let payout = if market.state == MarketState::Resolved {
+        // Only the bet on the winning side pays, with its share of the losing pool
+        let winning_bet = match market.winning_outcome {
+            Outcome::Yes => position.yes_bet,
+            Outcome::No => position.no_bet,
+            Outcome::None => 0,
+        };
+        market.pari_mutuel_payout(winning_bet)?
     } else {
-        // Cancelled - full refund
-        payout = position
-            .yes_bet
-            .checked_add(position.no_bet)
-            .ok_or(PredictionMarketError::Overflow)?;
-    }
+        // Cancelled (including no-counterparty settlements) - full refund of bets and
+        // commitment
+        bets.checked_add(refundable_commitment)
+            .ok_or(PredictionMarketError::Overflow)?
+    };
 
     // Mark as claimed
     ctx.accounts.user_position.claimed = true;
@@ -115,6 +107,12 @@ pub fn handler(ctx: Context<ClaimWinnings>, market_id: u64) -> Result<()> {
         transfer(cpi_ctx, payout)?;
     }
 
+    let market = &mut ctx.accounts.market;
+    market.total_paid_out = market
+        .total_paid_out
+        .checked_add(payout)
+        .ok_or(PredictionMarketError::Overflow)?;
+
     msg!("Winnings claimed");
     msg!("Market ID: {}", market_id);
     msg!("User: {}", ctx.accounts.user.key());
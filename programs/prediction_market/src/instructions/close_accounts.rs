@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer,
+};
+
+use crate::error::PredictionMarketError;
+use crate::lifecycle::require_phase;
+use crate::state::{
+    BondStatus, Config, FixedOddsTerms, HousePool, Market, MarketMode, MarketState, MarketType,
+    UserPosition, UserStake,
+};
+
+/// Close a claimed, forfeited or fully exited position (or any position once the market
+/// was swept), returning its rent to the user
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct ClosePosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [UserPosition::SEED, market_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.claimed || user_position.is_empty() || market.swept @ PredictionMarketError::PositionNotSettled
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+/// Close a stake-limit record once its market has settled, returning its rent to the user
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct CloseUserStake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [UserStake::SEED, market_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+}
+
+/// Close a settled market once every position is closed and every share burned, or the
+/// vault has been swept, and the order book has been settled. Legacy markets do not
+/// count their positions and always wait for the sweep. Share mints cannot be
+/// closed and stay behind. Fixed-odds markets hand any reservation still held for
+/// unrevealed commits back to the house pool. Permissionless: leftover vault balance goes
+/// to the fee recipient and all rent goes to the creator.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct CloseMarket<'info> {
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized,
+        constraint = market.open_positions == 0 || market.swept @ PredictionMarketError::OpenPositionsRemain,
+        constraint = market.bond_status != BondStatus::Locked @ PredictionMarketError::BondStillLocked,
+        constraint = market.book_traders == 0 @ PredictionMarketError::OrderBookNotSettled
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [Market::BOND_VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bond_vault_bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    /// YES share mint (omitted for markets that never issued shares)
+    #[account(
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Option<Box<Account<'info, Mint>>>,
+
+    /// NO share mint (omitted for markets that never issued shares)
+    #[account(
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Option<Box<Account<'info, Mint>>>,
+
+    /// Fixed-odds terms (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [FixedOddsTerms::SEED, market_id.to_le_bytes().as_ref()],
+        bump = fixed_odds.bump
+    )]
+    pub fixed_odds: Option<Account<'info, FixedOddsTerms>>,
+
+    /// House pool (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [HousePool::SEED],
+        bump = house_pool.bump
+    )]
+    pub house_pool: Option<Account<'info, HousePool>>,
+
+    /// CHECK: Rent recipient, validated against market.creator
+    #[account(
+        mut,
+        address = market.creator
+    )]
+    pub creator: AccountInfo<'info>,
+
+    /// Protocol fee recipient token account (receives leftover vault balance)
+    #[account(
+        mut,
+        token::mint = market_vault.mint,
+        token::authority = config.fee_recipient
+    )]
+    pub fee_recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn close_position_handler(ctx: Context<ClosePosition>, market_id: u64) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    market.open_positions = market.open_positions.saturating_sub(1);
+
+    msg!("Position closed");
+    msg!("Market ID: {}", market_id);
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Open Positions: {}", market.open_positions);
+
+    Ok(())
+}
+
+pub fn close_user_stake_handler(ctx: Context<CloseUserStake>, market_id: u64) -> Result<()> {
+    msg!("Stake record closed");
+    msg!("Market ID: {}", market_id);
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Total Staked: {}", ctx.accounts.user_stake.total_staked);
+
+    Ok(())
+}
+
+pub fn close_market_handler(ctx: Context<CloseMarket>, market_id: u64) -> Result<()> {
+    let market = &ctx.accounts.market;
+    require_phase(
+        market,
+        Clock::get()?.unix_timestamp,
+        &[
+            MarketState::Resolved,
+            MarketState::Settled,
+            MarketState::Cancelled,
+            MarketState::Closed,
+        ],
+    )?;
+    // Legacy markets do not count their positions, so they wait for the sweep
+    require!(
+        market.mode != MarketMode::Legacy || market.swept,
+        PredictionMarketError::OpenPositionsRemain
+    );
+
+    // Shares are transferable, so open_positions cannot count their holders. Check the
+    // mint supplies instead, or that the market never issued shares if they are omitted.
+    let shares_outstanding = match (&ctx.accounts.yes_mint, &ctx.accounts.no_mint) {
+        (Some(yes_mint), Some(no_mint)) => yes_mint.supply > 0 || no_mint.supply > 0,
+        _ => {
+            market.yes_bet_shares > 0
+                || market.no_bet_shares > 0
+                || market.complete_sets > 0
+                || market.amm_collateral > 0
+        }
+    };
+    require!(
+        market.swept || !shares_outstanding,
+        PredictionMarketError::SharesOutstanding
+    );
+
+    // The market account goes away, so nothing could release the house's reservation
+    // for forfeited or unrevealed commits afterwards
+    let mut liability_released = 0;
+    if market.market_type == MarketType::FixedOdds {
+        let (Some(terms), Some(house_pool)) = (
+            ctx.accounts.fixed_odds.as_mut(),
+            ctx.accounts.house_pool.as_mut(),
+        ) else {
+            return err!(PredictionMarketError::MissingHousePool);
+        };
+        let reserved = terms.reserved_liability;
+        liability_released = terms.release_liability(house_pool, reserved);
+    }
+
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+
+    // Sweep what is left: pari-mutuel rounding dust and losing commit-reveal stakes
+    let dust = ctx.accounts.market_vault.amount;
+    if dust > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: ctx.accounts.fee_recipient_token_account.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, dust)?;
+    }
+
+    // An uncollected creation fee is all that can be left in the bond vault
+    let fee = ctx.accounts.bond_vault.amount;
+    if fee > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bond_vault.to_account_info(),
+            to: ctx.accounts.fee_recipient_token_account.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, fee)?;
+    }
+
+    // Close both token vaults, rent to the creator
+    for vault in [&ctx.accounts.market_vault, &ctx.accounts.bond_vault] {
+        let cpi_accounts = CloseAccount {
+            account: vault.to_account_info(),
+            destination: ctx.accounts.creator.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)?;
+    }
+
+    msg!("Market closed");
+    msg!("Market ID: {}", market_id);
+    msg!("Dust Swept: {}", dust);
+    msg!("Fee Collected: {}", fee);
+    msg!("House Liability Released: {}", liability_released);
+    msg!("Rent Recipient: {}", market.creator);
+
+    Ok(())
+}
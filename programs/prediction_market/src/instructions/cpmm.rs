@@ -0,0 +1,880 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    burn, mint_to, transfer, Burn, Mint, MintTo, Token, TokenAccount, Transfer,
+};
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::error::PredictionMarketError;
+use crate::lifecycle::require_phase;
+use crate::events::CpmmTrade;
+use crate::math::{cpmm_shares_in, cpmm_shares_out, cpmm_yes_price_bps};
+use crate::state::{
+    enforce_user_limits, Config, CpmmPool, Credential, Market, MarketMode, MarketState,
+    MarketType, Outcome, UserLimits,
+};
+
+/// Turn a fresh market into a constant-product market. Liquidity is added separately
+/// through add_liquidity.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct InitCpmmPool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CREATE) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = market.market_type == MarketType::PariMutuel @ PredictionMarketError::WrongMarketType,
+        constraint = market.mode == MarketMode::Public @ PredictionMarketError::WrongMarketMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + CpmmPool::INIT_SPACE,
+        seeds = [CpmmPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub cpmm_pool: Account<'info, CpmmPool>,
+
+    /// LP token mint, one LP token per unit of initial liquidity
+    #[account(
+        init,
+        payer = creator,
+        seeds = [CpmmPool::LP_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump,
+        mint::decimals = config.token_decimals,
+        mint::authority = market
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// Pool's YES reserve
+    #[account(
+        init,
+        payer = creator,
+        seeds = [CpmmPool::YES_RESERVE_SEED, market_id.to_le_bytes().as_ref()],
+        bump,
+        token::mint = yes_mint,
+        token::authority = market
+    )]
+    pub yes_reserve: Box<Account<'info, TokenAccount>>,
+
+    /// Pool's NO reserve
+    #[account(
+        init,
+        payer = creator,
+        seeds = [CpmmPool::NO_RESERVE_SEED, market_id.to_le_bytes().as_ref()],
+        bump,
+        token::mint = no_mint,
+        token::authority = market
+    )]
+    pub no_reserve: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Deposit collateral into a constant-product pool for LP tokens.
+///
+/// The deposit is split into complete sets. When the pool is unbalanced, the shares of
+/// the cheaper side that would change the price are returned to the provider.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct AddLiquidity<'info> {
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_BET) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.market_type == MarketType::Cpmm @ PredictionMarketError::WrongMarketType
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [CpmmPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.bump
+    )]
+    pub cpmm_pool: Account<'info, CpmmPool>,
+
+    #[account(
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::LP_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.lp_mint_bump
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::YES_RESERVE_SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.yes_reserve_bump
+    )]
+    pub yes_reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::NO_RESERVE_SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.no_reserve_bump
+    )]
+    pub no_reserve: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's LP token account
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = provider
+    )]
+    pub provider_lp_account: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's YES share account (receives returned shares)
+    #[account(
+        mut,
+        token::mint = yes_mint,
+        token::authority = provider
+    )]
+    pub provider_yes_account: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's NO share account (receives returned shares)
+    #[account(
+        mut,
+        token::mint = no_mint,
+        token::authority = provider
+    )]
+    pub provider_no_account: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = provider
+    )]
+    pub provider_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Provider's deposit limits; may not exist. Seeds are checked here and the
+    /// account is deserialized in the handler when it does.
+    #[account(
+        mut,
+        seeds = [UserLimits::SEED, provider.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+
+    /// Provider's credential (markets with a minimum tier only)
+    #[account(
+        seeds = [Credential::SEED, provider.key().as_ref()],
+        bump = credential.bump
+    )]
+    pub credential: Option<Box<Account<'info, Credential>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Burn LP tokens for a proportional cut of the pool's YES and NO reserves. Allowed at
+/// any time; after settlement the shares are redeemed through redeem_shares.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct RemoveLiquidity<'info> {
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.market_type == MarketType::Cpmm @ PredictionMarketError::WrongMarketType
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [CpmmPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.bump
+    )]
+    pub cpmm_pool: Account<'info, CpmmPool>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::LP_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.lp_mint_bump
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::YES_RESERVE_SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.yes_reserve_bump
+    )]
+    pub yes_reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::NO_RESERVE_SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.no_reserve_bump
+    )]
+    pub no_reserve: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's LP token account
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = provider
+    )]
+    pub provider_lp_account: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's YES share account
+    #[account(
+        mut,
+        token::mint = yes_reserve.mint,
+        token::authority = provider
+    )]
+    pub provider_yes_account: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's NO share account
+    #[account(
+        mut,
+        token::mint = no_reserve.mint,
+        token::authority = provider
+    )]
+    pub provider_no_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Trade outcome shares against a constant-product pool. Used by both buy and sell.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct TradeCpmm<'info> {
+    pub trader: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.market_type == MarketType::Cpmm @ PredictionMarketError::WrongMarketType
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.bump
+    )]
+    pub cpmm_pool: Account<'info, CpmmPool>,
+
+    #[account(
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::YES_RESERVE_SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.yes_reserve_bump
+    )]
+    pub yes_reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [CpmmPool::NO_RESERVE_SEED, market_id.to_le_bytes().as_ref()],
+        bump = cpmm_pool.no_reserve_bump
+    )]
+    pub no_reserve: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's token account for the traded outcome's shares
+    #[account(
+        mut,
+        token::authority = trader
+    )]
+    pub trader_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = trader
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Trader's deposit limits; may not exist. Seeds are checked here and the
+    /// account is deserialized in the handler when it does.
+    #[account(
+        mut,
+        seeds = [UserLimits::SEED, trader.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+
+    /// Trader's credential (markets with a minimum tier only)
+    #[account(
+        seeds = [Credential::SEED, trader.key().as_ref()],
+        bump = credential.bump
+    )]
+    pub credential: Option<Box<Account<'info, Credential>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> TradeCpmm<'info> {
+    /// Validate the trade and return the (own, other) reserve accounts for the outcome
+    fn reserves(
+        &self,
+        outcome: Outcome,
+    ) -> Result<(&Account<'info, TokenAccount>, &Account<'info, TokenAccount>)> {
+        require!(
+            outcome == Outcome::Yes || outcome == Outcome::No,
+            PredictionMarketError::InvalidOutcome
+        );
+        let clock = Clock::get()?;
+        require_phase(&self.market, clock.unix_timestamp, &[MarketState::Active])?;
+
+        let (own, other) = if outcome == Outcome::Yes {
+            (&self.yes_reserve, &self.no_reserve)
+        } else {
+            (&self.no_reserve, &self.yes_reserve)
+        };
+        require!(
+            self.trader_share_account.mint == own.mint,
+            PredictionMarketError::InvalidShareMint
+        );
+        require!(
+            own.amount > 0 && other.amount > 0,
+            PredictionMarketError::InsufficientLiquidity
+        );
+        Ok((own, other))
+    }
+
+    /// Mint `amount` complete sets into the pool reserves
+    fn mint_sets_to_reserves(&self, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        for (mint, reserve) in [
+            (&self.yes_mint, &self.yes_reserve),
+            (&self.no_mint, &self.no_reserve),
+        ] {
+            let cpi_accounts = MintTo {
+                mint: mint.to_account_info(),
+                to: reserve.to_account_info(),
+                authority: self.market.to_account_info(),
+            };
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            mint_to(cpi_ctx, amount)?;
+        }
+        Ok(())
+    }
+
+    /// Burn `amount` complete sets from the pool reserves
+    fn burn_sets_from_reserves(&self, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        for (mint, reserve) in [
+            (&self.yes_mint, &self.yes_reserve),
+            (&self.no_mint, &self.no_reserve),
+        ] {
+            let cpi_accounts = Burn {
+                mint: mint.to_account_info(),
+                from: reserve.to_account_info(),
+                authority: self.market.to_account_info(),
+            };
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            burn(cpi_ctx, amount)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn init_cpmm_pool_handler(
+    ctx: Context<InitCpmmPool>,
+    market_id: u64,
+    fee_bps: u16,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    require!(
+        fee_bps <= market.config_max_fee_bps,
+        PredictionMarketError::InvalidFee
+    );
+    let clock = Clock::get()?;
+    require_phase(market, clock.unix_timestamp, &[MarketState::Active])?;
+    require!(
+        market.share_pot()? == 0 && market.yes_bettors == 0 && market.no_bettors == 0,
+        PredictionMarketError::MarketNotEmpty
+    );
+
+    let pool = &mut ctx.accounts.cpmm_pool;
+    pool.market_id = market_id;
+    pool.fee_bps = fee_bps;
+    pool.total_fees = 0;
+    pool.lp_mint_bump = ctx.bumps.lp_mint;
+    pool.yes_reserve_bump = ctx.bumps.yes_reserve;
+    pool.no_reserve_bump = ctx.bumps.no_reserve;
+    pool.bump = ctx.bumps.cpmm_pool;
+
+    ctx.accounts.market.market_type = MarketType::Cpmm;
+
+    msg!("CPMM pool opened");
+    msg!("Market ID: {}", market_id);
+    msg!("Fee BPS: {}", fee_bps);
+
+    Ok(())
+}
+
+pub fn add_liquidity_handler(
+    ctx: Context<AddLiquidity>,
+    market_id: u64,
+    amount: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(amount > 0, PredictionMarketError::ZeroAmount);
+    let clock = Clock::get()?;
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
+    ctx.accounts
+        .market
+        .check_allowlisted(&ctx.accounts.provider.key(), &allowlist_proof)?;
+    ctx.accounts.market.check_credential(
+        ctx.accounts.credential.as_deref().map(|credential| &**credential),
+        &ctx.accounts.config.attester,
+        clock.unix_timestamp,
+    )?;
+    enforce_user_limits(&ctx.accounts.user_limits, amount)?;
+
+    // The first deposit sets 50/50 odds; later deposits keep the current price by
+    // leaving only part of the cheaper side's shares in the pool
+    let yes_balance = ctx.accounts.yes_reserve.amount;
+    let no_balance = ctx.accounts.no_reserve.amount;
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    let (lp_tokens, yes_returned, no_returned) = if lp_supply == 0 {
+        (amount, 0, 0)
+    } else {
+        let pool_weight = yes_balance.max(no_balance) as u128;
+        let kept = |balance: u64| -> Result<u64> {
+            let kept = (amount as u128)
+                .checked_mul(balance as u128)
+                .ok_or(PredictionMarketError::Overflow)?
+                .checked_div(pool_weight)
+                .ok_or(PredictionMarketError::InsufficientLiquidity)?;
+            Ok(kept as u64)
+        };
+        let lp_tokens = (amount as u128)
+            .checked_mul(lp_supply as u128)
+            .ok_or(PredictionMarketError::Overflow)?
+            .checked_div(pool_weight)
+            .ok_or(PredictionMarketError::InsufficientLiquidity)?;
+        (
+            u64::try_from(lp_tokens).map_err(|_| PredictionMarketError::Overflow)?,
+            amount - kept(yes_balance)?,
+            amount - kept(no_balance)?,
+        )
+    };
+    require!(lp_tokens > 0, PredictionMarketError::ZeroAmount);
+
+    // Take the collateral
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.provider_token_account.to_account_info(),
+        to: ctx.accounts.market_vault.to_account_info(),
+        authority: ctx.accounts.provider.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer(cpi_ctx, amount)?;
+
+    let market = &ctx.accounts.market;
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    // Split it into complete sets: kept shares go to the reserves, the rest to the provider
+    let mints = [
+        (
+            &ctx.accounts.yes_mint,
+            &ctx.accounts.yes_reserve,
+            &ctx.accounts.provider_yes_account,
+            yes_returned,
+        ),
+        (
+            &ctx.accounts.no_mint,
+            &ctx.accounts.no_reserve,
+            &ctx.accounts.provider_no_account,
+            no_returned,
+        ),
+    ];
+    for (mint, reserve, provider_account, returned) in mints {
+        for (to, shares) in [
+            (reserve.to_account_info(), amount - returned),
+            (provider_account.to_account_info(), returned),
+        ] {
+            if shares == 0 {
+                continue;
+            }
+            let cpi_accounts = MintTo {
+                mint: mint.to_account_info(),
+                to,
+                authority: market.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            mint_to(cpi_ctx, shares)?;
+        }
+    }
+
+    // Mint LP tokens
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        to: ctx.accounts.provider_lp_account.to_account_info(),
+        authority: market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    mint_to(cpi_ctx, lp_tokens)?;
+
+    let market = &mut ctx.accounts.market;
+    market.complete_sets = market
+        .complete_sets
+        .checked_add(amount)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    msg!("Liquidity added");
+    msg!("Market ID: {}", market_id);
+    msg!("Provider: {}", ctx.accounts.provider.key());
+    msg!("Amount: {}", amount);
+    msg!("LP Tokens: {}", lp_tokens);
+    msg!("YES Returned: {}", yes_returned);
+    msg!("NO Returned: {}", no_returned);
+
+    Ok(())
+}
+
+pub fn remove_liquidity_handler(
+    ctx: Context<RemoveLiquidity>,
+    market_id: u64,
+    lp_amount: u64,
+) -> Result<()> {
+    require!(lp_amount > 0, PredictionMarketError::ZeroAmount);
+
+    let lp_supply = ctx.accounts.lp_mint.supply as u128;
+    let share_of = |balance: u64| -> Result<u64> {
+        let share = (lp_amount as u128)
+            .checked_mul(balance as u128)
+            .ok_or(PredictionMarketError::Overflow)?
+            .checked_div(lp_supply)
+            .ok_or(PredictionMarketError::InsufficientLiquidity)?;
+        Ok(share as u64)
+    };
+    let yes_out = share_of(ctx.accounts.yes_reserve.amount)?;
+    let no_out = share_of(ctx.accounts.no_reserve.amount)?;
+
+    // Burn LP tokens
+    let cpi_accounts = Burn {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        from: ctx.accounts.provider_lp_account.to_account_info(),
+        authority: ctx.accounts.provider.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    burn(cpi_ctx, lp_amount)?;
+
+    // Hand over the provider's cut of the reserves
+    let market = &ctx.accounts.market;
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    for (reserve, provider_account, shares) in [
+        (&ctx.accounts.yes_reserve, &ctx.accounts.provider_yes_account, yes_out),
+        (&ctx.accounts.no_reserve, &ctx.accounts.provider_no_account, no_out),
+    ] {
+        if shares == 0 {
+            continue;
+        }
+        let cpi_accounts = Transfer {
+            from: reserve.to_account_info(),
+            to: provider_account.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, shares)?;
+    }
+
+    msg!("Liquidity removed");
+    msg!("Market ID: {}", market_id);
+    msg!("Provider: {}", ctx.accounts.provider.key());
+    msg!("LP Tokens: {}", lp_amount);
+    msg!("YES Out: {}", yes_out);
+    msg!("NO Out: {}", no_out);
+
+    Ok(())
+}
+
+pub fn buy_cpmm_shares_handler(
+    ctx: Context<TradeCpmm>,
+    market_id: u64,
+    outcome: Outcome,
+    investment: u64,
+    min_shares_out: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        !ctx.accounts.config.is_paused(Config::PAUSE_BET),
+        PredictionMarketError::Paused
+    );
+    require!(investment > 0, PredictionMarketError::ZeroAmount);
+    ctx.accounts
+        .market
+        .check_allowlisted(&ctx.accounts.trader.key(), &allowlist_proof)?;
+    ctx.accounts.market.check_credential(
+        ctx.accounts.credential.as_deref().map(|credential| &**credential),
+        &ctx.accounts.config.attester,
+        Clock::get()?.unix_timestamp,
+    )?;
+    enforce_user_limits(&ctx.accounts.user_limits, investment)?;
+    let (own_reserve, other_reserve) = ctx.accounts.reserves(outcome)?;
+
+    // The fee stays in the pool as extra complete sets for liquidity providers
+    let fee = (investment as u128)
+        .checked_mul(ctx.accounts.cpmm_pool.fee_bps as u128)
+        .ok_or(PredictionMarketError::Overflow)?
+        .checked_div(BPS_DENOMINATOR as u128)
+        .ok_or(PredictionMarketError::Overflow)? as u64;
+    let shares_out = cpmm_shares_out(
+        own_reserve.amount,
+        other_reserve.amount,
+        investment - fee,
+    )?;
+    require!(
+        shares_out >= min_shares_out,
+        PredictionMarketError::TradeLimitExceeded
+    );
+    let own_reserve_info = own_reserve.to_account_info();
+
+    // Take payment
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.trader_token_account.to_account_info(),
+        to: ctx.accounts.market_vault.to_account_info(),
+        authority: ctx.accounts.trader.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer(cpi_ctx, investment)?;
+
+    // Split the full investment into complete sets and pay out the bought side
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[ctx.accounts.market.bump]];
+    let signer_seeds = &[&seeds[..]];
+    ctx.accounts.mint_sets_to_reserves(investment, signer_seeds)?;
+
+    let cpi_accounts = Transfer {
+        from: own_reserve_info,
+        to: ctx.accounts.trader_share_account.to_account_info(),
+        authority: ctx.accounts.market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    transfer(cpi_ctx, shares_out)?;
+
+    let market = &mut ctx.accounts.market;
+    market.complete_sets = market
+        .complete_sets
+        .checked_add(investment)
+        .ok_or(PredictionMarketError::Overflow)?;
+    let pool = &mut ctx.accounts.cpmm_pool;
+    pool.total_fees = pool
+        .total_fees
+        .checked_add(fee)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    ctx.accounts.yes_reserve.reload()?;
+    ctx.accounts.no_reserve.reload()?;
+    let yes_price_bps =
+        cpmm_yes_price_bps(ctx.accounts.yes_reserve.amount, ctx.accounts.no_reserve.amount);
+    emit!(CpmmTrade {
+        market_id,
+        trader: ctx.accounts.trader.key(),
+        outcome,
+        is_buy: true,
+        shares: shares_out,
+        amount: investment,
+        fee,
+        yes_price_bps,
+    });
+
+    msg!("CPMM shares bought");
+    msg!("Market ID: {}", market_id);
+    msg!("Outcome: {:?}", outcome);
+    msg!("Investment: {}", investment);
+    msg!("Shares: {}", shares_out);
+    msg!("Fee: {}", fee);
+    msg!("YES Price (bps): {}", yes_price_bps);
+
+    Ok(())
+}
+
+pub fn sell_cpmm_shares_handler(
+    ctx: Context<TradeCpmm>,
+    market_id: u64,
+    outcome: Outcome,
+    return_amount: u64,
+    max_shares_in: u64,
+) -> Result<()> {
+    require!(
+        !ctx.accounts.config.is_paused(Config::PAUSE_CLAIM),
+        PredictionMarketError::Paused
+    );
+    require!(return_amount > 0, PredictionMarketError::ZeroAmount);
+    let (own_reserve, other_reserve) = ctx.accounts.reserves(outcome)?;
+
+    // Release enough complete sets to cover the return plus the fee; the fee's sets
+    // stay in the pool for liquidity providers
+    let fee_bps = ctx.accounts.cpmm_pool.fee_bps as u128;
+    let gross_return = (return_amount as u128)
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .ok_or(PredictionMarketError::Overflow)?
+        .div_ceil(BPS_DENOMINATOR as u128 - fee_bps);
+    let gross_return = u64::try_from(gross_return).map_err(|_| PredictionMarketError::Overflow)?;
+    let fee = gross_return - return_amount;
+    let shares_in = cpmm_shares_in(own_reserve.amount, other_reserve.amount, gross_return)?;
+    require!(
+        shares_in <= max_shares_in,
+        PredictionMarketError::TradeLimitExceeded
+    );
+    let own_reserve_info = own_reserve.to_account_info();
+
+    // Take the shares into the pool
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.trader_share_account.to_account_info(),
+        to: own_reserve_info,
+        authority: ctx.accounts.trader.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer(cpi_ctx, shares_in)?;
+
+    // Merge complete sets back into collateral and pay the trader
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[ctx.accounts.market.bump]];
+    let signer_seeds = &[&seeds[..]];
+    ctx.accounts.burn_sets_from_reserves(return_amount, signer_seeds)?;
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.market_vault.to_account_info(),
+        to: ctx.accounts.trader_token_account.to_account_info(),
+        authority: ctx.accounts.market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    transfer(cpi_ctx, return_amount)?;
+
+    let market = &mut ctx.accounts.market;
+    market.complete_sets = market
+        .complete_sets
+        .checked_sub(return_amount)
+        .ok_or(PredictionMarketError::InsufficientCompleteSets)?;
+    let pool = &mut ctx.accounts.cpmm_pool;
+    pool.total_fees = pool
+        .total_fees
+        .checked_add(fee)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    ctx.accounts.yes_reserve.reload()?;
+    ctx.accounts.no_reserve.reload()?;
+    let yes_price_bps =
+        cpmm_yes_price_bps(ctx.accounts.yes_reserve.amount, ctx.accounts.no_reserve.amount);
+    emit!(CpmmTrade {
+        market_id,
+        trader: ctx.accounts.trader.key(),
+        outcome,
+        is_buy: false,
+        shares: shares_in,
+        amount: return_amount,
+        fee,
+        yes_price_bps,
+    });
+
+    msg!("CPMM shares sold");
+    msg!("Market ID: {}", market_id);
+    msg!("Outcome: {:?}", outcome);
+    msg!("Return: {}", return_amount);
+    msg!("Shares: {}", shares_in);
+    msg!("Fee: {}", fee);
+    msg!("YES Price (bps): {}", yes_price_bps);
+
+    Ok(())
+}
@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
 
-use crate::constants::{ALLOWED_CREATORS, MAX_QUESTION_LENGTH, SOL_USD_FEED_ID};
+use crate::constants::{MAX_QUESTION_LENGTH, SOL_USD_FEED_ID};
 use crate::error::PredictionMarketError;
-use crate::state::{Config, Market, MarketState, Outcome};
+use crate::state::{
+    AllowedCreator, BondStatus, CancelReason, Config, Market, MarketMode, MarketState, MarketType,
+    Outcome,
+};
  use anchor_spl::associated_token::AssociatedToken; 
 #[derive(Accounts)]
 pub struct CreateMarket<'info> {
@@ -14,10 +17,20 @@ pub struct CreateMarket<'info> {
         mut,
         seeds = [Config::SEED],
         bump = config.bump,
-        constraint = !config.paused @ PredictionMarketError::Paused
+        constraint = !config.is_paused(Config::PAUSE_CREATE) @ PredictionMarketError::Paused
     )]
     pub config: Account<'info, Config>,
 
+    /// Allowlist entry for the creator. When omitted, the creator must post a bond.
+    #[account(
+        mut,
+        seeds = [AllowedCreator::SEED, creator.key().as_ref()],
+        bump = allowed_creator.bump,
+        constraint = allowed_creator.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = !allowed_creator.deactivated @ PredictionMarketError::UnauthorizedCreator
+    )]
+    pub allowed_creator: Option<Account<'info, AllowedCreator>>,
+
     #[account(
         init,
         payer = creator,
@@ -38,26 +51,54 @@ pub struct CreateMarket<'info> {
     )]
     pub market_vault: Account<'info, TokenAccount>,
 
+    /// Bond vault holding the creator bond until the market settles, and the creation fee
+    /// until the protocol collects it
+    #[account(
+        init,
+        payer = creator,
+        seeds = [Market::BOND_VAULT_SEED, (config.market_counter + 1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = market
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    /// YES outcome share mint, minted 1:1 against collateral by the market
+    #[account(
+        init,
+        payer = creator,
+        seeds = [Market::YES_MINT_SEED, (config.market_counter + 1).to_le_bytes().as_ref()],
+        bump,
+        mint::decimals = config.token_decimals,
+        mint::authority = market
+    )]
+    pub yes_mint: Box<Account<'info, anchor_spl::token::Mint>>,
+
+    /// NO outcome share mint, minted 1:1 against collateral by the market
+    #[account(
+        init,
+        payer = creator,
+        seeds = [Market::NO_MINT_SEED, (config.market_counter + 1).to_le_bytes().as_ref()],
+        bump,
+        mint::decimals = config.token_decimals,
+        mint::authority = market
+    )]
+    pub no_mint: Box<Account<'info, anchor_spl::token::Mint>>,
+
     /// Token mint
     #[account(
         constraint = token_mint.key() == config.token_mint
     )]
     pub token_mint: Account<'info, anchor_spl::token::Mint>,
 
-    /// Creator's token account (for fee payment)
+    /// Creator's token account (for fee and bond payment)
     #[account(
+        mut,
         token::mint = token_mint,
         token::authority = creator
     )]
     pub creator_token_account: Account<'info, TokenAccount>,
 
-    /// Fee recipient's token account
-    #[account(
-        mut,
-        token::mint = token_mint
-    )]
-    pub fee_recipient_token_account: Account<'info, TokenAccount>,
-
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,   
@@ -72,13 +113,33 @@ pub fn handler(
     resolution_time: i64,
     fee_amount: u64,
     price_threshold: i64,
+    mode: MarketMode,
 ) -> Result<()> {
-    // Check if creator is whitelisted
-    let creator_key = ctx.accounts.creator.key();
-    let is_allowed = ALLOWED_CREATORS.iter().any(|&allowed| {
-        creator_key.to_string() == allowed
-    });
-    require!(is_allowed, PredictionMarketError::UnauthorizedCreator);
+    // Allowlisted creators are checked against their limits; everyone else posts a bond
+    let bond_amount = match ctx.accounts.allowed_creator.as_mut() {
+        Some(allowed_creator) => {
+            require!(
+                allowed_creator.has_capacity(),
+                PredictionMarketError::CreatorMarketLimitReached
+            );
+            require!(
+                allowed_creator.is_feed_allowed(&SOL_USD_FEED_ID),
+                PredictionMarketError::FeedNotAllowed
+            );
+            allowed_creator.open_markets = allowed_creator
+                .open_markets
+                .checked_add(1)
+                .ok_or(PredictionMarketError::Overflow)?;
+            0
+        }
+        None => {
+            require!(
+                ctx.accounts.config.permissionless_creation,
+                PredictionMarketError::PermissionlessCreationDisabled
+            );
+            ctx.accounts.config.creator_bond_amount
+        }
+    };
 
     // Validations
     require!(!question.is_empty(), PredictionMarketError::EmptyQuestion);
@@ -93,6 +154,12 @@ pub fn handler(
         PredictionMarketError::InvalidResolutionTime
     );
 
+    // Legacy mode only describes markets created before modes existed
+    require!(
+        mode != MarketMode::Legacy,
+        PredictionMarketError::WrongMarketMode
+    );
+
     // Validate price threshold (must be positive)
     require!(
         price_threshold > 0,
@@ -103,11 +170,12 @@ pub fn handler(
     msg!("Price feed ID: {:?}", SOL_USD_FEED_ID);
     msg!("Price threshold: {}", price_threshold);
 
-    // Transfer fee if applicable
+    // Escrow the fee, if any, so the creator can get it back by cancelling before
+    // anyone enters
     if fee_amount > 0 {
         let cpi_accounts = Transfer {
             from: ctx.accounts.creator_token_account.to_account_info(),
-            to: ctx.accounts.fee_recipient_token_account.to_account_info(),
+            to: ctx.accounts.bond_vault.to_account_info(),
             authority: ctx.accounts.creator.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
@@ -115,6 +183,18 @@ pub fn handler(
         transfer(cpi_ctx, fee_amount)?;
     }
 
+    // Lock creator bond
+    if bond_amount > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.creator_token_account.to_account_info(),
+            to: ctx.accounts.bond_vault.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        transfer(cpi_ctx, bond_amount)?;
+    }
+
     // Increment market counter
     let config = &mut ctx.accounts.config;
     config.market_counter = config
@@ -142,15 +222,62 @@ pub fn handler(
     market.pyth_price_feed_id = Some(SOL_USD_FEED_ID);
     market.price_threshold = Some(price_threshold);
     market.reveal_deadline = 0; // Set when market is resolved
+    market.bond_amount = bond_amount;
+    market.bond_status = if bond_amount > 0 {
+        BondStatus::Locked
+    } else {
+        BondStatus::None
+    };
+    market.bond_vault_bump = ctx.bumps.bond_vault;
+    market.paused = false;
+    market.version = Market::CURRENT_VERSION;
+    market.open_positions = 0;
+    market.claim_expiry = 0; // Set when market is resolved or cancelled
+    market.swept = false;
+    market.total_committed = 0;
+    market.total_revealed_yes = 0;
+    market.total_revealed_no = 0;
+    market.total_paid_out = 0;
+    market.total_forfeited = 0;
+    market.total_fees = 0;
+    market.commit_count = 0;
+    market.cancel_reason = CancelReason::None;
+    market.exit_fee_bps = config.exit_fee_bps;
+    market.yes_bettors = 0;
+    market.no_bettors = 0;
+    market.yes_mint_bump = ctx.bumps.yes_mint;
+    market.no_mint_bump = ctx.bumps.no_mint;
+    market.yes_bet_shares = 0;
+    market.no_bet_shares = 0;
+    market.complete_sets = 0;
+    market.share_payouts = 0;
+    market.market_type = MarketType::PariMutuel;
+    market.amm_collateral = 0;
+    market.book_traders = 0;
+    market.min_bet = 0;
+    market.max_bet_per_user = 0;
+    market.max_total_pool = 0;
+    market.allowlist_root = None;
+    market.min_tier = 0;
+    market.mode = mode;
+    market.disputed_at = 0;
+    market.fee_escrow = fee_amount;
+    market.cancel_code = 0;
+    market.exit_fees = 0;
+    market.holds_creator_slot = ctx.accounts.allowed_creator.is_some();
+    market.was_disputed = false;
+    market.dispute_deadline = 0; // Set when market is resolved
 
     msg!("Market created");
     msg!("Market ID: {}", market.id);
+    msg!("Mode: {:?}", market.mode);
     msg!("Question: {}", market.question);
     msg!("Resolution Time: {}", market.resolution_time);
     msg!("Pyth Oracle: Enabled (SOL/USD)");
     msg!("  Feed ID: {:?}", SOL_USD_FEED_ID);
     msg!("  Price Threshold: {}", price_threshold);
     msg!("  Resolution: If SOL price >= threshold, YES wins; else NO wins");
+    msg!("Creator Bond: {}", market.bond_amount);
 
     Ok(())
 }
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+
+use crate::error::PredictionMarketError;
+use crate::state::{AllowedCreator, Config, Role, MAX_CREATOR_FEEDS};
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct AddCreator<'info> {
+    #[account(
+        mut,
+        constraint = config.has_role(Role::CreatorManager, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + AllowedCreator::INIT_SPACE,
+        seeds = [AllowedCreator::SEED, creator.as_ref()],
+        bump
+    )]
+    pub allowed_creator: Account<'info, AllowedCreator>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct RemoveCreator<'info> {
+    #[account(
+        mut,
+        constraint = config.has_role(Role::CreatorManager, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [AllowedCreator::SEED, creator.as_ref()],
+        bump = allowed_creator.bump
+    )]
+    pub allowed_creator: Account<'info, AllowedCreator>,
+}
+
+/// Add a creator to the allowlist, or update the limits of an existing entry and
+/// reactivate it if it was removed
+pub fn add_creator_handler(
+    ctx: Context<AddCreator>,
+    creator: Pubkey,
+    max_open_markets: u32,
+    allowed_feeds: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        allowed_feeds.len() <= MAX_CREATOR_FEEDS,
+        PredictionMarketError::TooManyCreatorFeeds
+    );
+
+    let entry = &mut ctx.accounts.allowed_creator;
+    if entry.creator == Pubkey::default() {
+        // New entry
+        entry.creator = creator;
+        entry.open_markets = 0;
+        entry.bump = ctx.bumps.allowed_creator;
+    }
+    entry.max_open_markets = max_open_markets;
+    entry.allowed_feeds = allowed_feeds;
+    entry.deactivated = false;
+
+    msg!("Creator allowed");
+    msg!("Creator: {}", entry.creator);
+    msg!("Max Open Markets: {}", entry.max_open_markets);
+    msg!("Allowed Feeds: {}", entry.allowed_feeds.len());
+
+    Ok(())
+}
+
+/// Remove a creator from the allowlist. The entry is deactivated rather than closed so
+/// its open market count survives until those markets settle.
+pub fn remove_creator_handler(ctx: Context<RemoveCreator>, creator: Pubkey) -> Result<()> {
+    ctx.accounts.allowed_creator.deactivated = true;
+
+    msg!("Creator removed");
+    msg!("Creator: {}", creator);
+    msg!("Open Markets: {}", ctx.accounts.allowed_creator.open_markets);
+
+    Ok(())
+}
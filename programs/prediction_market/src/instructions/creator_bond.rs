@@ -0,0 +1,239 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::constants::BOND_RELEASE_DELAY_SECONDS;
+use crate::error::PredictionMarketError;
+use crate::lifecycle::require_phase;
+use crate::state::{BondStatus, Config, Market, MarketState, Role};
+
+/// Slash a creator bond for a malformed or misleading market.
+/// Creator-manager-only instruction that sends the bond to the protocol fee recipient.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SlashBond<'info> {
+    #[account(
+        constraint = config.has_role(Role::CreatorManager, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.bond_status == BondStatus::Locked @ PredictionMarketError::BondNotLocked
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::BOND_VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bond_vault_bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    /// Protocol fee recipient token account (receives the slashed bond)
+    #[account(
+        mut,
+        token::mint = bond_vault.mint,
+        token::authority = config.fee_recipient
+    )]
+    pub fee_recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Return a creator bond once the market has settled and the slashing window has passed.
+/// Permissionless: the bond can only ever go to the market creator.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct ReleaseBond<'info> {
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized,
+        constraint = market.bond_status == BondStatus::Locked @ PredictionMarketError::BondNotLocked
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::BOND_VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bond_vault_bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    /// Creator's token account (receives the bond)
+    #[account(
+        mut,
+        token::mint = bond_vault.mint,
+        token::authority = market.creator
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Send a settled market's escrowed creation fee to the protocol fee recipient.
+/// Permissionless: the fee can only ever go to the fee recipient.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct CollectCreationFee<'info> {
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.fee_escrow > 0 @ PredictionMarketError::NoEscrowedFee
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::BOND_VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bond_vault_bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    /// Protocol fee recipient token account (receives the fee)
+    #[account(
+        mut,
+        token::mint = bond_vault.mint,
+        token::authority = config.fee_recipient
+    )]
+    pub fee_recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn slash_bond_handler(ctx: Context<SlashBond>, market_id: u64) -> Result<()> {
+    let amount = ctx.accounts.market.bond_amount;
+    transfer_bond(
+        &ctx.accounts.market,
+        &ctx.accounts.bond_vault,
+        ctx.accounts.fee_recipient_token_account.to_account_info(),
+        &ctx.accounts.token_program,
+        market_id,
+        amount,
+    )?;
+
+    ctx.accounts.market.bond_status = BondStatus::Slashed;
+
+    msg!("Creator bond slashed");
+    msg!("Market ID: {}", market_id);
+    msg!("Amount: {}", amount);
+
+    Ok(())
+}
+
+pub fn release_bond_handler(ctx: Context<ReleaseBond>, market_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    // A disputed market may still turn out to deserve slashing
+    require_phase(
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+        &[
+            MarketState::Resolved,
+            MarketState::Settled,
+            MarketState::Cancelled,
+            MarketState::Closed,
+        ],
+    )?;
+    let release_time = ctx
+        .accounts
+        .market
+        .resolution_time
+        .checked_add(BOND_RELEASE_DELAY_SECONDS)
+        .ok_or(PredictionMarketError::Overflow)?;
+    require!(
+        clock.unix_timestamp >= release_time,
+        PredictionMarketError::BondReleaseTooEarly
+    );
+
+    let amount = ctx.accounts.market.bond_amount;
+    transfer_bond(
+        &ctx.accounts.market,
+        &ctx.accounts.bond_vault,
+        ctx.accounts.creator_token_account.to_account_info(),
+        &ctx.accounts.token_program,
+        market_id,
+        amount,
+    )?;
+
+    ctx.accounts.market.bond_status = BondStatus::Released;
+
+    msg!("Creator bond released");
+    msg!("Market ID: {}", market_id);
+    msg!("Creator: {}", ctx.accounts.market.creator);
+    msg!("Amount: {}", amount);
+
+    Ok(())
+}
+
+pub fn collect_creation_fee_handler(
+    ctx: Context<CollectCreationFee>,
+    market_id: u64,
+) -> Result<()> {
+    // The creator can only reclaim the fee while the market is unresolved
+    require_phase(
+        &ctx.accounts.market,
+        Clock::get()?.unix_timestamp,
+        &[
+            MarketState::Resolved,
+            MarketState::Settled,
+            MarketState::Cancelled,
+            MarketState::Closed,
+        ],
+    )?;
+
+    let amount = ctx.accounts.market.fee_escrow;
+    transfer_bond(
+        &ctx.accounts.market,
+        &ctx.accounts.bond_vault,
+        ctx.accounts.fee_recipient_token_account.to_account_info(),
+        &ctx.accounts.token_program,
+        market_id,
+        amount,
+    )?;
+
+    ctx.accounts.market.fee_escrow = 0;
+
+    msg!("Creation fee collected");
+    msg!("Market ID: {}", market_id);
+    msg!("Amount: {}", amount);
+
+    Ok(())
+}
+
+fn transfer_bond<'info>(
+    market: &Account<'info, Market>,
+    bond_vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    market_id: u64,
+    amount: u64,
+) -> Result<()> {
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: bond_vault.to_account_info(),
+        to,
+        authority: market.to_account_info(),
+    };
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+    transfer(cpi_ctx, amount)
+}
@@ -0,0 +1,368 @@
+use anchor_lang::prelude::*;
+use solana_instructions_sysvar::{get_instruction_relative, ID as INSTRUCTIONS_SYSVAR_ID};
+
+use crate::error::PredictionMarketError;
+use crate::state::{Config, Credential, Market, MarketState};
+
+/// Ed25519 instruction layout: signature count, padding, then one offsets entry per
+/// signature. `u16::MAX` as an instruction index means "this instruction".
+const ED25519_HEADER_LEN: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_PUBKEY_LEN: usize = 32;
+const ED25519_SIGNATURE_LEN: usize = 64;
+
+/// Native ed25519 signature verification program
+const ED25519_PROGRAM_ID: Pubkey = pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+/// Record an attester-signed credential for the signer.
+///
+/// The transaction must carry an ed25519 program instruction right before this one,
+/// verifying the attester's signature over `Credential::message`.
+#[derive(Accounts)]
+pub struct RecordCredential<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        space = 8 + Credential::INIT_SPACE,
+        seeds = [Credential::SEED, wallet.key().as_ref()],
+        bump
+    )]
+    pub credential: Account<'info, Credential>,
+
+    /// CHECK: Instructions sysvar, checked by address
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Revoke a wallet's credential (attester only). Also blocks recording any credential
+/// issued before now, so it works on credentials that were never recorded.
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct RevokeCredential<'info> {
+    #[account(
+        mut,
+        constraint = config.attester != Pubkey::default() @ PredictionMarketError::NoAttester,
+        constraint = attester.key() == config.attester @ PredictionMarketError::NotAttester
+    )]
+    pub attester: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = attester,
+        space = 8 + Credential::INIT_SPACE,
+        seeds = [Credential::SEED, wallet.as_ref()],
+        bump
+    )]
+    pub credential: Account<'info, Credential>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Set the credential tier a market requires (creator only, 0 = none)
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SetMinTier<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive
+    )]
+    pub market: Account<'info, Market>,
+}
+
+/// Read a little-endian u16 at `offset`
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .ok_or(PredictionMarketError::InvalidAttestation)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// `len` bytes of `data` starting at the u16 offset stored at `offset_field`
+fn read_slice(data: &[u8], offset_field: usize, len: usize) -> Result<&[u8]> {
+    let start = read_u16(data, offset_field)? as usize;
+    data.get(start..start + len)
+        .ok_or(PredictionMarketError::InvalidAttestation.into())
+}
+
+/// Check that the previous instruction is an ed25519 verification of exactly one
+/// signature by `signer` over `message`, with all data inside that instruction.
+fn verify_ed25519_instruction(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let ix = get_instruction_relative(-1, instructions)
+        .map_err(|_| PredictionMarketError::InvalidAttestation)?;
+    require_keys_eq!(
+        ix.program_id,
+        ED25519_PROGRAM_ID,
+        PredictionMarketError::InvalidAttestation
+    );
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        PredictionMarketError::InvalidAttestation
+    );
+
+    // Offsets: signature, its ix index, pubkey, its ix index, message, size, its ix index
+    let offsets = ED25519_HEADER_LEN;
+    for index_field in [offsets + 2, offsets + 6, offsets + 12] {
+        require!(
+            read_u16(data, index_field)? == u16::MAX,
+            PredictionMarketError::InvalidAttestation
+        );
+    }
+    read_slice(data, offsets, ED25519_SIGNATURE_LEN)?;
+    let pubkey = read_slice(data, offsets + 4, ED25519_PUBKEY_LEN)?;
+    let message_len = read_u16(data, offsets + 10)? as usize;
+    let signed_message = read_slice(data, offsets + 8, message_len)?;
+
+    require!(
+        pubkey == signer.as_ref() && signed_message == message,
+        PredictionMarketError::InvalidAttestation
+    );
+    Ok(())
+}
+
+pub fn record_credential_handler(
+    ctx: Context<RecordCredential>,
+    tier: u8,
+    issued_at: i64,
+    expires_at: i64,
+) -> Result<()> {
+    let attester = ctx.accounts.config.attester;
+    require!(
+        attester != Pubkey::default(),
+        PredictionMarketError::NoAttester
+    );
+    let wallet = ctx.accounts.wallet.key();
+    let message = Credential::message(&wallet, tier, issued_at, expires_at);
+    verify_ed25519_instruction(&ctx.accounts.instructions, &attester, &message)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let credential = &mut ctx.accounts.credential;
+    require!(
+        credential.accepts(&attester, issued_at, expires_at, now),
+        PredictionMarketError::StaleCredential
+    );
+
+    credential.wallet = wallet;
+    credential.attester = attester;
+    credential.tier = tier;
+    credential.issued_at = issued_at;
+    credential.expires_at = expires_at;
+    credential.bump = ctx.bumps.credential;
+
+    msg!("Credential recorded");
+    msg!("Wallet: {}", wallet);
+    msg!("Tier: {}", tier);
+    msg!("Expires At: {}", expires_at);
+
+    Ok(())
+}
+
+pub fn revoke_credential_handler(ctx: Context<RevokeCredential>, wallet: Pubkey) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let credential = &mut ctx.accounts.credential;
+    credential.wallet = wallet;
+    credential.attester = ctx.accounts.attester.key();
+    credential.revoked_at = now;
+    credential.bump = ctx.bumps.credential;
+
+    msg!("Credential revoked");
+    msg!("Wallet: {}", wallet);
+    msg!("Revoked At: {}", now);
+
+    Ok(())
+}
+
+pub fn set_min_tier_handler(ctx: Context<SetMinTier>, market_id: u64, min_tier: u8) -> Result<()> {
+    ctx.accounts.market.min_tier = min_tier;
+
+    msg!("Market credential tier updated");
+    msg!("Market ID: {}", market_id);
+    msg!("Min Tier: {}", min_tier);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_instruction::BorrowedInstruction;
+    use solana_instructions_sysvar::{construct_instructions_data, store_current_index_checked};
+
+    use crate::state::test_utils::zeroed;
+
+    const NOW: i64 = 1_700_000_000;
+
+    /// Ed25519 instruction data for one signature by `pubkey` over `message`, with every
+    /// offset pointing into the instruction itself
+    fn ed25519_data(pubkey: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let pubkey_offset = (ED25519_HEADER_LEN + ED25519_OFFSETS_LEN) as u16;
+        let signature_offset = pubkey_offset + ED25519_PUBKEY_LEN as u16;
+        let message_offset = signature_offset + ED25519_SIGNATURE_LEN as u16;
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            u16::MAX,
+            pubkey_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(pubkey.as_ref());
+        data.extend_from_slice(&[0u8; ED25519_SIGNATURE_LEN]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    /// Run `verify_ed25519_instruction` as the second instruction of a transaction whose
+    /// first instruction is `previous`
+    fn verify_after(
+        program_id: &Pubkey,
+        previous: &[u8],
+        signer: &Pubkey,
+        message: &[u8],
+    ) -> Result<()> {
+        let this_program = crate::ID;
+        let instructions = [
+            BorrowedInstruction {
+                program_id,
+                accounts: vec![],
+                data: previous,
+            },
+            BorrowedInstruction {
+                program_id: &this_program,
+                accounts: vec![],
+                data: &[],
+            },
+        ];
+        let mut data = construct_instructions_data(&instructions);
+        store_current_index_checked(&mut data, 1).unwrap();
+        let mut lamports = 0;
+        let owner = Pubkey::default();
+        let account = AccountInfo::new(
+            &INSTRUCTIONS_SYSVAR_ID,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        verify_ed25519_instruction(&account, signer, message)
+    }
+
+    #[test]
+    fn accepts_attestation_signed_by_the_attester() {
+        let attester = Pubkey::new_unique();
+        let message = Credential::message(&Pubkey::new_unique(), 2, NOW, NOW + 100);
+        let data = ed25519_data(&attester, &message);
+        assert!(verify_after(&ED25519_PROGRAM_ID, &data, &attester, &message).is_ok());
+
+        let credential: Credential = zeroed(Credential::INIT_SPACE);
+        assert!(credential.accepts(&attester, NOW, NOW + 100, NOW));
+    }
+
+    #[test]
+    fn rejects_attestation_by_another_signer() {
+        let attester = Pubkey::new_unique();
+        let message = Credential::message(&Pubkey::new_unique(), 2, NOW, NOW + 100);
+        let data = ed25519_data(&Pubkey::new_unique(), &message);
+        assert!(verify_after(&ED25519_PROGRAM_ID, &data, &attester, &message).is_err());
+    }
+
+    #[test]
+    fn rejects_attestation_over_another_message_or_program() {
+        let attester = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let signed = Credential::message(&wallet, 1, NOW, NOW + 100);
+        let data = ed25519_data(&attester, &signed);
+
+        // A lower-tier signature does not verify a higher tier
+        let claimed = Credential::message(&wallet, 3, NOW, NOW + 100);
+        assert!(verify_after(&ED25519_PROGRAM_ID, &data, &attester, &claimed).is_err());
+        // The data only counts if the ed25519 program checked it
+        assert!(verify_after(&crate::ID, &data, &attester, &signed).is_err());
+    }
+
+    #[test]
+    fn rejects_stale_signature() {
+        let attester = Pubkey::new_unique();
+        let mut credential: Credential = zeroed(Credential::INIT_SPACE);
+        credential.wallet = Pubkey::new_unique();
+        credential.attester = attester;
+        credential.tier = 1;
+        credential.issued_at = NOW;
+        credential.expires_at = NOW + 100;
+
+        // Replaying the recorded signature, or an older one, cannot undo a downgrade
+        assert!(!credential.accepts(&attester, NOW, NOW + 100, NOW + 1));
+        assert!(!credential.accepts(&attester, NOW - 10, NOW + 100, NOW + 1));
+        // An expired signature is refused even if it is newer
+        assert!(!credential.accepts(&attester, NOW + 1, NOW + 1, NOW + 1));
+        assert!(credential.accepts(&attester, NOW + 1, NOW + 100, NOW + 1));
+    }
+
+    #[test]
+    fn rejects_credential_issued_before_revocation() {
+        let attester = Pubkey::new_unique();
+        let mut credential: Credential = zeroed(Credential::INIT_SPACE);
+        credential.wallet = Pubkey::new_unique();
+        credential.attester = attester;
+        credential.tier = 2;
+        credential.issued_at = NOW;
+        credential.expires_at = NOW + 100;
+        credential.revoked_at = NOW + 10;
+        assert_eq!(credential.effective_tier(&attester, NOW + 20), 0);
+
+        // Signatures from before the revocation stay dead, newer ones restore the wallet
+        assert!(!credential.accepts(&attester, NOW + 5, NOW + 100, NOW + 20));
+        assert!(!credential.accepts(&attester, NOW + 10, NOW + 100, NOW + 20));
+        assert!(credential.accepts(&attester, NOW + 15, NOW + 100, NOW + 20));
+    }
+
+    #[test]
+    fn rejects_future_dated_credential() {
+        let attester = Pubkey::new_unique();
+        let mut credential: Credential = zeroed(Credential::INIT_SPACE);
+        assert!(!credential.accepts(&attester, NOW + 50, NOW + 100, NOW));
+        assert!(credential.accepts(&attester, NOW, NOW + 100, NOW));
+
+        // Had it been recorded, revoking the wallet before its issue date would not stick
+        credential.wallet = Pubkey::new_unique();
+        credential.attester = attester;
+        credential.tier = 2;
+        credential.issued_at = NOW + 50;
+        credential.expires_at = NOW + 100;
+        credential.revoked_at = NOW + 10;
+        assert_eq!(credential.effective_tier(&attester, NOW + 20), 2);
+    }
+}
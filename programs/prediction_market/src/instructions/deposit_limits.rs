@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{LIMIT_COOLING_OFF_SECONDS, MAX_SELF_EXCLUSION_SECONDS};
+use crate::error::PredictionMarketError;
+use crate::state::UserLimits;
+
+/// Create or update the signer's deposit limits or self-exclusion
+#[derive(Accounts)]
+pub struct SetUserLimits<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserLimits::INIT_SPACE,
+        seeds = [UserLimits::SEED, user.key().as_ref()],
+        bump
+    )]
+    pub user_limits: Account<'info, UserLimits>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Initialize a freshly created limits account
+fn init_if_new(limits: &mut UserLimits, user: Pubkey, bump: u8) {
+    if limits.user == Pubkey::default() {
+        limits.user = user;
+        limits.bump = bump;
+    }
+}
+
+pub fn set_deposit_limits_handler(
+    ctx: Context<SetUserLimits>,
+    daily_cap: u64,
+    weekly_cap: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let limits = &mut ctx.accounts.user_limits;
+    init_if_new(limits, ctx.accounts.user.key(), ctx.bumps.user_limits);
+    limits.apply_due_changes(now);
+
+    // Tighter caps apply now; looser ones replace whatever is pending and wait out the
+    // cooling-off period
+    let daily_raise = UserLimits::is_raise(limits.daily_cap, daily_cap);
+    let weekly_raise = UserLimits::is_raise(limits.weekly_cap, weekly_cap);
+    limits.pending_daily_cap = None;
+    limits.pending_weekly_cap = None;
+    if daily_raise {
+        limits.pending_daily_cap = Some(daily_cap);
+    } else {
+        limits.daily_cap = daily_cap;
+    }
+    if weekly_raise {
+        limits.pending_weekly_cap = Some(weekly_cap);
+    } else {
+        limits.weekly_cap = weekly_cap;
+    }
+    if daily_raise || weekly_raise {
+        limits.pending_effective_at = now
+            .checked_add(LIMIT_COOLING_OFF_SECONDS)
+            .ok_or(PredictionMarketError::Overflow)?;
+    }
+
+    msg!("Deposit limits updated");
+    msg!("User: {}", limits.user);
+    msg!("Daily Cap: {}", limits.daily_cap);
+    msg!("Weekly Cap: {}", limits.weekly_cap);
+    if daily_raise || weekly_raise {
+        msg!("Pending Daily Cap: {:?}", limits.pending_daily_cap);
+        msg!("Pending Weekly Cap: {:?}", limits.pending_weekly_cap);
+        msg!("Pending Effective At: {}", limits.pending_effective_at);
+    }
+
+    Ok(())
+}
+
+pub fn self_exclude_handler(ctx: Context<SetUserLimits>, duration: i64) -> Result<()> {
+    require!(
+        duration > 0 && duration <= MAX_SELF_EXCLUSION_SECONDS,
+        PredictionMarketError::InvalidExclusionPeriod
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let limits = &mut ctx.accounts.user_limits;
+    init_if_new(limits, ctx.accounts.user.key(), ctx.bumps.user_limits);
+
+    // An exclusion can only ever be extended, never shortened
+    let excluded_until = now
+        .checked_add(duration)
+        .ok_or(PredictionMarketError::Overflow)?;
+    limits.excluded_until = limits.excluded_until.max(excluded_until);
+
+    msg!("Self-exclusion set");
+    msg!("User: {}", limits.user);
+    msg!("Excluded Until: {}", limits.excluded_until);
+
+    Ok(())
+}
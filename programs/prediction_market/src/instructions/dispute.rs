@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+
+use crate::error::PredictionMarketError;
+use crate::lifecycle::transition;
+use crate::state::{CancelReason, Config, Market, MarketState, Role};
+
+/// Contest a market's resolution during the dispute window after it (canceller), once per
+/// market. Freezes reveals, claims, redemptions and forfeits until the admin settles the
+/// dispute.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct DisputeMarket<'info> {
+    #[account(
+        constraint = config.has_role(Role::Canceller, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+}
+
+/// Uphold or overturn a disputed resolution (admin only)
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SettleDispute<'info> {
+    #[account(
+        constraint = admin.key() == config.admin @ PredictionMarketError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn dispute_market_handler(ctx: Context<DisputeMarket>, market_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let market = &mut ctx.accounts.market;
+    transition(market, MarketState::Disputed, clock.unix_timestamp)?;
+
+    msg!("Market disputed");
+    msg!("Market ID: {}", market_id);
+    msg!("Disputed By: {}", ctx.accounts.authority.key());
+
+    Ok(())
+}
+
+pub fn settle_dispute_handler(
+    ctx: Context<SettleDispute>,
+    market_id: u64,
+    uphold: bool,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let market = &mut ctx.accounts.market;
+    if uphold {
+        transition(market, MarketState::Resolved, clock.unix_timestamp)?;
+    } else {
+        transition(market, MarketState::Cancelled, clock.unix_timestamp)?;
+        market.cancel_reason = CancelReason::Overturned;
+    }
+
+    msg!("Dispute settled");
+    msg!("Market ID: {}", market_id);
+    msg!("Upheld: {}", uphold);
+    msg!("Reveal Deadline: {}", market.reveal_deadline);
+    msg!("Claim Expiry: {}", market.claim_expiry);
+
+    Ok(())
+}
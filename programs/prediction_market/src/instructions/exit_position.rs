@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, transfer, Burn, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::error::PredictionMarketError;
+use crate::lifecycle::require_phase;
+use crate::state::{Config, Market, MarketMode, MarketState, MarketType, Outcome};
+
+/// Withdraw some or all of a pari-mutuel bet before the betting cutoff by burning
+/// outcome shares.
+///
+/// The market's exit fee is kept in the vault and tracked apart from the pools. It is
+/// added to the winners' payouts on resolution. On cancellation bettors are refunded
+/// their remaining stakes only, and the protocol keeps the fees: sweep_market or
+/// close_market sends them to the fee recipient.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct ExitPosition<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.market_type == MarketType::PariMutuel @ PredictionMarketError::WrongMarketType,
+        constraint = market.mode != MarketMode::Private @ PredictionMarketError::WrongMarketMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// Bettor's token account holding the shares being exited
+    #[account(
+        mut,
+        token::authority = bettor
+    )]
+    pub bettor_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// Bettor's token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = bettor
+    )]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(
+    ctx: Context<ExitPosition>,
+    market_id: u64,
+    outcome: Outcome,
+    amount: u64,
+) -> Result<()> {
+    // Validations
+    require!(amount > 0, PredictionMarketError::ZeroAmount);
+    require!(
+        outcome == Outcome::Yes || outcome == Outcome::No,
+        PredictionMarketError::InvalidOutcome
+    );
+
+    let clock = Clock::get()?;
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
+
+    let share_mint = if outcome == Outcome::Yes {
+        ctx.accounts.yes_mint.to_account_info()
+    } else {
+        ctx.accounts.no_mint.to_account_info()
+    };
+    require!(
+        ctx.accounts.bettor_share_account.mint == share_mint.key(),
+        PredictionMarketError::InvalidShareMint
+    );
+
+    // Burn the exited shares
+    let cpi_accounts = Burn {
+        mint: share_mint,
+        from: ctx.accounts.bettor_share_account.to_account_info(),
+        authority: ctx.accounts.bettor.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    burn(cpi_ctx, amount)?;
+
+    let market = &mut ctx.accounts.market;
+
+    let fee = (amount as u128)
+        .checked_mul(market.exit_fee_bps as u128)
+        .ok_or(PredictionMarketError::Overflow)?
+        .checked_div(BPS_DENOMINATOR as u128)
+        .ok_or(PredictionMarketError::Overflow)? as u64;
+    let net_amount = amount
+        .checked_sub(fee)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    // Reduce the bet shares and pool and keep the exit fee aside. Exits are capped by
+    // the shares place_bet has minted on that side.
+    if outcome == Outcome::Yes {
+        market.yes_bet_shares = market
+            .yes_bet_shares
+            .checked_sub(amount)
+            .ok_or(PredictionMarketError::InsufficientPosition)?;
+        market.yes_pool = market
+            .yes_pool
+            .checked_sub(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+    } else {
+        market.no_bet_shares = market
+            .no_bet_shares
+            .checked_sub(amount)
+            .ok_or(PredictionMarketError::InsufficientPosition)?;
+        market.no_pool = market
+            .no_pool
+            .checked_sub(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+    }
+    market.exit_fees = market
+        .exit_fees
+        .checked_add(fee)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    // Return the stake net of the exit fee. Pools are net of exits, so this is not
+    // counted in total_paid_out.
+    if net_amount > 0 {
+        let market_id_bytes = market_id.to_le_bytes();
+        let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: ctx.accounts.bettor_token_account.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, net_amount)?;
+    }
+
+    msg!("Position exited");
+    msg!("Market ID: {}", market_id);
+    msg!("Outcome: {:?}", outcome);
+    msg!("Amount: {}", amount);
+    msg!("Exit Fee: {}", fee);
+    msg!("Exit Fees: {}", market.exit_fees);
+    msg!("YES Pool: {}", market.yes_pool);
+    msg!("NO Pool: {}", market.no_pool);
+
+    Ok(())
+}
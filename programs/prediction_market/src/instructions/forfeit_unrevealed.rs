@@ -2,21 +2,27 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
 
 use crate::error::PredictionMarketError;
-use crate::state::{Config, Market, MarketState, UserPosition};
+use crate::lifecycle::require_phase;
+use crate::state::{
+    Config, FixedOddsTerms, HousePool, Market, MarketMode, MarketState, MarketType, Role,
+    UserPosition,
+};
 
 /// Forfeit unrevealed stakes to protocol after reveal deadline has passed.
-/// Admin-only instruction that transfers unrevealed committed stakes to protocol fee recipient.
+/// Resolver-only instruction that transfers unrevealed committed stakes to protocol fee recipient,
+/// or to the house pool in fixed-odds markets, where the house took the other side.
 #[derive(Accounts)]
 #[instruction(market_id: u64)]
 pub struct ForfeitUnrevealed<'info> {
     #[account(
-        constraint = admin.key() == config.admin @ PredictionMarketError::InvalidAdmin
+        constraint = config.has_role(Role::Resolver, &authority.key()) @ PredictionMarketError::MissingRole
     )]
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
 
     #[account(
         seeds = [Config::SEED],
-        bump = config.bump
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_FORFEIT) @ PredictionMarketError::Paused
     )]
     pub config: Account<'info, Config>,
 
@@ -24,7 +30,9 @@ pub struct ForfeitUnrevealed<'info> {
         mut,
         seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
         bump = market.bump,
-        constraint = market.state == MarketState::Resolved @ PredictionMarketError::MarketNotFinalized
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = !market.swept @ PredictionMarketError::MarketSwept,
+        constraint = market.mode != MarketMode::Public @ PredictionMarketError::WrongMarketMode
     )]
     pub market: Account<'info, Market>,
 
@@ -50,13 +58,38 @@ pub struct ForfeitUnrevealed<'info> {
     /// CHECK: Validated via user_position.user
     pub user_to_forfeit: AccountInfo<'info>,
 
-    /// Protocol fee recipient token account (receives forfeited stakes)
+    /// Protocol fee recipient token account (receives forfeited stakes outside
+    /// fixed-odds markets)
     #[account(
         mut,
         token::mint = market_vault.mint
     )]
     pub fee_recipient_token_account: Account<'info, TokenAccount>,
 
+    /// Fixed-odds terms (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [FixedOddsTerms::SEED, market_id.to_le_bytes().as_ref()],
+        bump = fixed_odds.bump
+    )]
+    pub fixed_odds: Option<Account<'info, FixedOddsTerms>>,
+
+    /// House pool (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [HousePool::SEED],
+        bump = house_pool.bump
+    )]
+    pub house_pool: Option<Account<'info, HousePool>>,
+
+    /// House vault (fixed-odds markets only, receives forfeited stakes)
+    #[account(
+        mut,
+        seeds = [HousePool::VAULT_SEED],
+        bump
+    )]
+    pub house_vault: Option<Box<Account<'info, TokenAccount>>>,
+
     pub token_program: Program<'info, Token>,
 }
 
@@ -65,21 +98,12 @@ pub fn handler(ctx: Context<ForfeitUnrevealed>, market_id: u64) -> Result<()> {
     let position = &mut ctx.accounts.user_position;
     let clock = Clock::get()?;
 
-    // Ensure market is resolved
-    require!(
-        market.state == MarketState::Resolved,
-        PredictionMarketError::MarketNotFinalized
-    );
-
-    // Ensure reveal deadline has passed
-    require!(
-        market.reveal_deadline > 0,
-        PredictionMarketError::MarketNotFinalized
-    );
-    require!(
-        clock.unix_timestamp > market.reveal_deadline,
-        PredictionMarketError::RevealDeadlineNotPassed
-    );
+    // Ensure market is resolved and the reveal deadline has passed
+    require_phase(
+        market,
+        clock.unix_timestamp,
+        &[MarketState::Settled, MarketState::Closed],
+    )?;
 
     // Ensure user hasn't revealed
     require!(
@@ -102,25 +126,51 @@ pub fn handler(ctx: Context<ForfeitUnrevealed>, market_id: u64) -> Result<()> {
     position.revealed = true;
     position.revealed_outcome = crate::state::Outcome::None;
 
-    // Transfer forfeited stakes to protocol fee recipient
+    // Transfer forfeited stakes to protocol fee recipient, or to the house in fixed-odds
+    // markets, which no longer needs to reserve the commit's winnings
+    let recipient = if market.market_type == MarketType::FixedOdds {
+        let (Some(terms), Some(house_pool), Some(house_vault)) = (
+            ctx.accounts.fixed_odds.as_mut(),
+            ctx.accounts.house_pool.as_mut(),
+            ctx.accounts.house_vault.as_ref(),
+        ) else {
+            return err!(PredictionMarketError::MissingHousePool);
+        };
+        let exposure = terms.house_exposure(forfeited_amount)?;
+        terms.release_liability(house_pool, exposure);
+        house_pool.total_received = house_pool
+            .total_received
+            .checked_add(forfeited_amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+        house_vault.to_account_info()
+    } else {
+        ctx.accounts.fee_recipient_token_account.to_account_info()
+    };
+
     let market_id_bytes = market_id.to_le_bytes();
     let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
     let signer_seeds = &[&seeds[..]];
 
     let cpi_accounts = Transfer {
         from: ctx.accounts.market_vault.to_account_info(),
-        to: ctx.accounts.fee_recipient_token_account.to_account_info(),
+        to: recipient.clone(),
         authority: ctx.accounts.market.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
     transfer(cpi_ctx, forfeited_amount)?;
 
+    let market = &mut ctx.accounts.market;
+    market.total_forfeited = market
+        .total_forfeited
+        .checked_add(forfeited_amount)
+        .ok_or(PredictionMarketError::Overflow)?;
+
     msg!("Unrevealed stake forfeited");
     msg!("Market ID: {}", market_id);
     msg!("User: {}", ctx.accounts.user_to_forfeit.key());
     msg!("Forfeited Amount: {}", forfeited_amount);
-    msg!("Fee Recipient: {}", ctx.accounts.fee_recipient_token_account.key());
+    msg!("Recipient: {}", recipient.key());
 
     Ok(())
 }
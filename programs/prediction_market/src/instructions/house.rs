@@ -0,0 +1,421 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, mint_to, transfer, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+
+use crate::constants::{BPS_DENOMINATOR, MAX_FIXED_ODDS_BPS};
+use crate::error::PredictionMarketError;
+use crate::lifecycle::{phase, require_phase};
+use crate::state::{
+    enforce_user_limits, Config, FixedOddsTerms, HousePool, Market, MarketMode, MarketState,
+    MarketType, Role, UserLimits,
+};
+
+/// Open the protocol's house liquidity pool (admin only)
+#[derive(Accounts)]
+pub struct InitHousePool<'info> {
+    #[account(
+        mut,
+        constraint = config.admin == admin.key() @ PredictionMarketError::InvalidAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + HousePool::INIT_SPACE,
+        seeds = [HousePool::SEED],
+        bump
+    )]
+    pub house_pool: Account<'info, HousePool>,
+
+    #[account(
+        constraint = token_mint.key() == config.token_mint
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// House vault holding LP collateral and losing fixed-odds stakes
+    #[account(
+        init,
+        payer = admin,
+        seeds = [HousePool::VAULT_SEED],
+        bump,
+        token::mint = token_mint,
+        token::authority = house_pool
+    )]
+    pub house_vault: Box<Account<'info, TokenAccount>>,
+
+    /// House LP token mint
+    #[account(
+        init,
+        payer = admin,
+        seeds = [HousePool::LP_MINT_SEED],
+        bump,
+        mint::decimals = config.token_decimals,
+        mint::authority = house_pool
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Deposit collateral into the house pool for LP tokens priced at the vault's current
+/// value per LP token
+#[derive(Accounts)]
+pub struct DepositHouseLiquidity<'info> {
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_BET) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [HousePool::SEED],
+        bump = house_pool.bump
+    )]
+    pub house_pool: Account<'info, HousePool>,
+
+    #[account(
+        mut,
+        seeds = [HousePool::VAULT_SEED],
+        bump = house_pool.vault_bump
+    )]
+    pub house_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [HousePool::LP_MINT_SEED],
+        bump = house_pool.lp_mint_bump
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    /// Provider's LP token account
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = provider
+    )]
+    pub provider_lp_account: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = provider
+    )]
+    pub provider_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Provider's deposit limits; may not exist. Seeds are checked here and the
+    /// account is deserialized in the handler when it does.
+    #[account(
+        mut,
+        seeds = [UserLimits::SEED, provider.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Burn house LP tokens for their share of the vault. Liquidity reserved for open
+/// fixed-odds commits cannot be withdrawn.
+#[derive(Accounts)]
+pub struct WithdrawHouseLiquidity<'info> {
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [HousePool::SEED],
+        bump = house_pool.bump
+    )]
+    pub house_pool: Account<'info, HousePool>,
+
+    #[account(
+        mut,
+        seeds = [HousePool::VAULT_SEED],
+        bump = house_pool.vault_bump
+    )]
+    pub house_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [HousePool::LP_MINT_SEED],
+        bump = house_pool.lp_mint_bump
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    /// Provider's LP token account
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = provider
+    )]
+    pub provider_lp_account: Box<Account<'info, TokenAccount>>,
+
+    /// Provider's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = provider
+    )]
+    pub provider_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Turn a fresh market into a fixed-odds market backed by the house pool. Only possible
+/// before any bets, shares or commits exist. The house's LPs carry the risk, so the odds
+/// and liability cap are co-signed by the creator manager.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct InitFixedOdds<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        constraint = config.has_role(Role::CreatorManager, &approver.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub approver: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CREATE) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = market.market_type == MarketType::PariMutuel @ PredictionMarketError::WrongMarketType,
+        constraint = market.mode == MarketMode::Private @ PredictionMarketError::WrongMarketMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + FixedOddsTerms::INIT_SPACE,
+        seeds = [FixedOddsTerms::SEED, market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub fixed_odds: Account<'info, FixedOddsTerms>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Return a settled fixed-odds market's remaining reservation (unrevealed commits) to
+/// the house pool. Permissionless; allowed once the market is cancelled or its reveal
+/// deadline has passed.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct ReleaseFixedOddsLiability<'info> {
+    #[account(
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [FixedOddsTerms::SEED, market_id.to_le_bytes().as_ref()],
+        bump = fixed_odds.bump
+    )]
+    pub fixed_odds: Account<'info, FixedOddsTerms>,
+
+    #[account(
+        mut,
+        seeds = [HousePool::SEED],
+        bump = house_pool.bump
+    )]
+    pub house_pool: Account<'info, HousePool>,
+}
+
+pub fn init_house_pool_handler(ctx: Context<InitHousePool>) -> Result<()> {
+    let pool = &mut ctx.accounts.house_pool;
+    pool.total_reserved = 0;
+    pool.total_paid = 0;
+    pool.total_received = 0;
+    pool.lp_mint_bump = ctx.bumps.lp_mint;
+    pool.vault_bump = ctx.bumps.house_vault;
+    pool.bump = ctx.bumps.house_pool;
+
+    msg!("House pool opened");
+    msg!("LP Mint: {}", ctx.accounts.lp_mint.key());
+
+    Ok(())
+}
+
+pub fn deposit_house_liquidity_handler(ctx: Context<DepositHouseLiquidity>, amount: u64) -> Result<()> {
+    require!(amount > 0, PredictionMarketError::ZeroAmount);
+    enforce_user_limits(&ctx.accounts.user_limits, amount)?;
+
+    let vault_balance = ctx.accounts.house_vault.amount;
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    let lp_tokens = if lp_supply == 0 {
+        amount
+    } else {
+        let lp_tokens = (amount as u128)
+            .checked_mul(lp_supply as u128)
+            .ok_or(PredictionMarketError::Overflow)?
+            .checked_div(vault_balance as u128)
+            .ok_or(PredictionMarketError::InsufficientHouseLiquidity)?;
+        u64::try_from(lp_tokens).map_err(|_| PredictionMarketError::Overflow)?
+    };
+    require!(lp_tokens > 0, PredictionMarketError::ZeroAmount);
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.provider_token_account.to_account_info(),
+        to: ctx.accounts.house_vault.to_account_info(),
+        authority: ctx.accounts.provider.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer(cpi_ctx, amount)?;
+
+    let seeds = &[HousePool::SEED, &[ctx.accounts.house_pool.bump]];
+    let signer_seeds = &[&seeds[..]];
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        to: ctx.accounts.provider_lp_account.to_account_info(),
+        authority: ctx.accounts.house_pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    mint_to(cpi_ctx, lp_tokens)?;
+
+    msg!("House liquidity added");
+    msg!("Provider: {}", ctx.accounts.provider.key());
+    msg!("Amount: {}", amount);
+    msg!("LP Tokens: {}", lp_tokens);
+
+    Ok(())
+}
+
+pub fn withdraw_house_liquidity_handler(
+    ctx: Context<WithdrawHouseLiquidity>,
+    lp_amount: u64,
+) -> Result<()> {
+    require!(lp_amount > 0, PredictionMarketError::ZeroAmount);
+
+    let vault_balance = ctx.accounts.house_vault.amount;
+    let amount = (lp_amount as u128)
+        .checked_mul(vault_balance as u128)
+        .ok_or(PredictionMarketError::Overflow)?
+        .checked_div(ctx.accounts.lp_mint.supply as u128)
+        .ok_or(PredictionMarketError::InsufficientHouseLiquidity)? as u64;
+    let unreserved = vault_balance.saturating_sub(ctx.accounts.house_pool.total_reserved);
+    require!(
+        amount <= unreserved,
+        PredictionMarketError::InsufficientHouseLiquidity
+    );
+
+    let cpi_accounts = Burn {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        from: ctx.accounts.provider_lp_account.to_account_info(),
+        authority: ctx.accounts.provider.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    burn(cpi_ctx, lp_amount)?;
+
+    if amount > 0 {
+        let seeds = &[HousePool::SEED, &[ctx.accounts.house_pool.bump]];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.house_vault.to_account_info(),
+            to: ctx.accounts.provider_token_account.to_account_info(),
+            authority: ctx.accounts.house_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+    }
+
+    msg!("House liquidity removed");
+    msg!("Provider: {}", ctx.accounts.provider.key());
+    msg!("LP Tokens: {}", lp_amount);
+    msg!("Amount: {}", amount);
+
+    Ok(())
+}
+
+pub fn init_fixed_odds_handler(
+    ctx: Context<InitFixedOdds>,
+    market_id: u64,
+    odds_bps: u32,
+    max_liability: u64,
+) -> Result<()> {
+    require!(
+        odds_bps as u64 > BPS_DENOMINATOR && odds_bps <= MAX_FIXED_ODDS_BPS,
+        PredictionMarketError::InvalidOdds
+    );
+    require!(max_liability > 0, PredictionMarketError::ZeroAmount);
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+    require_phase(market, clock.unix_timestamp, &[MarketState::Active])?;
+    require!(market.commit_count == 0, PredictionMarketError::MarketNotEmpty);
+
+    let terms = &mut ctx.accounts.fixed_odds;
+    terms.market_id = market_id;
+    terms.odds_bps = odds_bps;
+    terms.max_liability = max_liability;
+    terms.reserved_liability = 0;
+    terms.bump = ctx.bumps.fixed_odds;
+
+    ctx.accounts.market.market_type = MarketType::FixedOdds;
+
+    msg!("Fixed odds set");
+    msg!("Market ID: {}", market_id);
+    msg!("Odds BPS: {}", odds_bps);
+    msg!("Max Liability: {}", max_liability);
+    msg!("Approved By: {}", ctx.accounts.approver.key());
+
+    Ok(())
+}
+
+pub fn release_fixed_odds_liability_handler(
+    ctx: Context<ReleaseFixedOddsLiability>,
+    market_id: u64,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+    // Resolved commits can still be revealed (and paid) until the reveal deadline
+    require!(
+        matches!(
+            phase(market, clock.unix_timestamp),
+            MarketState::Cancelled | MarketState::Settled | MarketState::Closed
+        ),
+        PredictionMarketError::LiabilityNotReleasable
+    );
+
+    let terms = &mut ctx.accounts.fixed_odds;
+    let pool = &mut ctx.accounts.house_pool;
+    let reserved = terms.reserved_liability;
+    let released = terms.release_liability(pool, reserved);
+
+    msg!("Fixed-odds liability released");
+    msg!("Market ID: {}", market_id);
+    msg!("Released: {}", released);
+    msg!("House Reserved: {}", pool.total_reserved);
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
 
-use crate::constants::MAX_FEE_LIMIT;
+use crate::constants::{DEFAULT_MAX_PRICE_AGE_SECONDS, DEFAULT_TIMELOCK_DELAY_SECONDS, MAX_FEE_LIMIT};
 use crate::error::PredictionMarketError;
 use crate::state::Config;
 
@@ -48,8 +48,23 @@ pub fn handler(ctx: Context<Initialize>, max_fee_bps: u16) -> Result<()> {
     config.token_decimals = ctx.accounts.token_mint.decimals;
     config.max_fee_bps = max_fee_bps;
     config.market_counter = 0;
-    config.paused = false;
+    config.pause_flags = 0;
     config.bump = ctx.bumps.config;
+    config.permissionless_creation = false;
+    config.creator_bond_amount = 0;
+    config.pending_admin = None;
+    config.pauser = Pubkey::default();
+    config.resolver = Pubkey::default();
+    config.canceller = Pubkey::default();
+    config.fee_manager = Pubkey::default();
+    config.creator_manager = Pubkey::default();
+    config.timelock_delay = DEFAULT_TIMELOCK_DELAY_SECONDS;
+    config.config_change_counter = 0;
+    config.max_price_age = DEFAULT_MAX_PRICE_AGE_SECONDS;
+    config.version = Config::CURRENT_VERSION;
+    config.exit_fee_bps = 0;
+    config.max_stake_per_wallet = 0;
+    config.attester = Pubkey::default();
 
     msg!("Prediction Market initialized");
     msg!("Admin: {}", config.admin);
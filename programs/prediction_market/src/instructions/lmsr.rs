@@ -0,0 +1,611 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    burn, mint_to, transfer, Burn, Mint, MintTo, Token, TokenAccount, Transfer,
+};
+
+use crate::error::PredictionMarketError;
+use crate::lifecycle::{require_dispute_window_passed, require_phase};
+use crate::events::LmsrTrade;
+use crate::math::lmsr_subsidy;
+use crate::state::{
+    enforce_user_limits, Config, Credential, LmsrPool, Market, MarketMode, MarketState,
+    MarketType, Outcome, UserLimits,
+};
+
+/// Turn a fresh market into an LMSR market. The creator deposits the subsidy `b * ln2`,
+/// which bounds the market maker's loss. Only possible before any bets or shares exist.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct InitLmsrPool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CREATE) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = market.market_type == MarketType::PariMutuel @ PredictionMarketError::WrongMarketType,
+        constraint = market.mode == MarketMode::Public @ PredictionMarketError::WrongMarketMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + LmsrPool::INIT_SPACE,
+        seeds = [LmsrPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub lmsr_pool: Account<'info, LmsrPool>,
+
+    /// Creator's token account (pays the subsidy)
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = creator
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Buy outcome shares from an LMSR market maker, paying `C(q + shares) - C(q)`.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct BuyLmsrShares<'info> {
+    pub trader: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_BET) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.market_type == MarketType::Lmsr @ PredictionMarketError::WrongMarketType
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [LmsrPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump = lmsr_pool.bump
+    )]
+    pub lmsr_pool: Account<'info, LmsrPool>,
+
+    #[account(
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// Trader's token account for the bought outcome's shares
+    #[account(
+        mut,
+        token::authority = trader
+    )]
+    pub trader_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = trader
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Trader's deposit limits; may not exist. Seeds are checked here and the
+    /// account is deserialized in the handler when it does.
+    #[account(
+        mut,
+        seeds = [UserLimits::SEED, trader.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+
+    /// Trader's credential (markets with a minimum tier only)
+    #[account(
+        seeds = [Credential::SEED, trader.key().as_ref()],
+        bump = credential.bump
+    )]
+    pub credential: Option<Box<Account<'info, Credential>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Sell outcome shares back to an LMSR market maker for `C(q) - C(q - shares)`.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SellLmsrShares<'info> {
+    pub trader: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.market_type == MarketType::Lmsr @ PredictionMarketError::WrongMarketType
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [LmsrPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump = lmsr_pool.bump
+    )]
+    pub lmsr_pool: Account<'info, LmsrPool>,
+
+    #[account(
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// Trader's token account holding the shares being sold
+    #[account(
+        mut,
+        token::authority = trader
+    )]
+    pub trader_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = trader
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Return the creator's subsidy once the market settles: on resolution whatever the
+/// market maker holds beyond the winning shares, on cancellation the full subsidy.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct WithdrawLmsrSubsidy<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [LmsrPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump = lmsr_pool.bump,
+        constraint = !lmsr_pool.subsidy_withdrawn @ PredictionMarketError::SubsidyAlreadyWithdrawn
+    )]
+    pub lmsr_pool: Account<'info, LmsrPool>,
+
+    #[account(
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// Creator's token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = creator
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn init_lmsr_pool_handler(
+    ctx: Context<InitLmsrPool>,
+    market_id: u64,
+    liquidity: u64,
+) -> Result<()> {
+    require!(liquidity > 0, PredictionMarketError::InvalidLiquidity);
+
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+    require_phase(market, clock.unix_timestamp, &[MarketState::Active])?;
+    require!(
+        market.share_pot()? == 0 && market.yes_bettors == 0 && market.no_bettors == 0,
+        PredictionMarketError::MarketNotEmpty
+    );
+
+    // Fund the market maker's worst-case loss
+    let subsidy = lmsr_subsidy(liquidity)?;
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.creator_token_account.to_account_info(),
+        to: ctx.accounts.market_vault.to_account_info(),
+        authority: ctx.accounts.creator.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer(cpi_ctx, subsidy)?;
+
+    let pool = &mut ctx.accounts.lmsr_pool;
+    pool.market_id = market_id;
+    pool.liquidity = liquidity;
+    pool.q_yes = 0;
+    pool.q_no = 0;
+    pool.subsidy = subsidy;
+    pool.subsidy_withdrawn = false;
+    pool.bump = ctx.bumps.lmsr_pool;
+
+    let market = &mut ctx.accounts.market;
+    market.market_type = MarketType::Lmsr;
+    market.amm_collateral = subsidy;
+
+    msg!("LMSR pool opened");
+    msg!("Market ID: {}", market_id);
+    msg!("Liquidity (b): {}", liquidity);
+    msg!("Subsidy: {}", subsidy);
+
+    Ok(())
+}
+
+pub fn buy_lmsr_shares_handler(
+    ctx: Context<BuyLmsrShares>,
+    market_id: u64,
+    outcome: Outcome,
+    shares: u64,
+    max_cost: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(shares > 0, PredictionMarketError::ZeroAmount);
+    require!(
+        outcome == Outcome::Yes || outcome == Outcome::No,
+        PredictionMarketError::InvalidOutcome
+    );
+    let clock = Clock::get()?;
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
+    ctx.accounts
+        .market
+        .check_allowlisted(&ctx.accounts.trader.key(), &allowlist_proof)?;
+    ctx.accounts.market.check_credential(
+        ctx.accounts.credential.as_deref().map(|credential| &**credential),
+        &ctx.accounts.config.attester,
+        clock.unix_timestamp,
+    )?;
+
+    let share_mint = if outcome == Outcome::Yes {
+        ctx.accounts.yes_mint.to_account_info()
+    } else {
+        ctx.accounts.no_mint.to_account_info()
+    };
+    require!(
+        ctx.accounts.trader_share_account.mint == share_mint.key(),
+        PredictionMarketError::InvalidShareMint
+    );
+
+    // Quote the cost function difference, rounded up in the pool's favor
+    let pool = &mut ctx.accounts.lmsr_pool;
+    let cost_before = pool.cost()?;
+    if outcome == Outcome::Yes {
+        pool.q_yes = pool
+            .q_yes
+            .checked_add(shares)
+            .ok_or(PredictionMarketError::Overflow)?;
+    } else {
+        pool.q_no = pool
+            .q_no
+            .checked_add(shares)
+            .ok_or(PredictionMarketError::Overflow)?;
+    }
+    let cost = pool
+        .cost()?
+        .checked_sub(cost_before)
+        .and_then(|diff| diff.checked_add(1))
+        .ok_or(PredictionMarketError::Overflow)?;
+    require!(cost <= max_cost, PredictionMarketError::TradeLimitExceeded);
+    enforce_user_limits(&ctx.accounts.user_limits, cost)?;
+
+    let market = &mut ctx.accounts.market;
+    market.amm_collateral = market
+        .amm_collateral
+        .checked_add(cost)
+        .ok_or(PredictionMarketError::Overflow)?;
+    require!(
+        market.amm_collateral >= pool.q_yes.max(pool.q_no),
+        PredictionMarketError::AmmInsolvent
+    );
+
+    // Take payment
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.trader_token_account.to_account_info(),
+        to: ctx.accounts.market_vault.to_account_info(),
+        authority: ctx.accounts.trader.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer(cpi_ctx, cost)?;
+
+    // Mint the shares
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = MintTo {
+        mint: share_mint,
+        to: ctx.accounts.trader_share_account.to_account_info(),
+        authority: market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    mint_to(cpi_ctx, shares)?;
+
+    let yes_price_bps = pool.yes_price_bps()?;
+    emit!(LmsrTrade {
+        market_id,
+        trader: ctx.accounts.trader.key(),
+        outcome,
+        is_buy: true,
+        shares,
+        amount: cost,
+        yes_price_bps,
+    });
+
+    msg!("LMSR shares bought");
+    msg!("Market ID: {}", market_id);
+    msg!("Outcome: {:?}", outcome);
+    msg!("Shares: {}", shares);
+    msg!("Cost: {}", cost);
+    msg!("YES Price (bps): {}", yes_price_bps);
+
+    Ok(())
+}
+
+pub fn sell_lmsr_shares_handler(
+    ctx: Context<SellLmsrShares>,
+    market_id: u64,
+    outcome: Outcome,
+    shares: u64,
+    min_proceeds: u64,
+) -> Result<()> {
+    require!(shares > 0, PredictionMarketError::ZeroAmount);
+    require!(
+        outcome == Outcome::Yes || outcome == Outcome::No,
+        PredictionMarketError::InvalidOutcome
+    );
+    let clock = Clock::get()?;
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
+
+    let share_mint = if outcome == Outcome::Yes {
+        ctx.accounts.yes_mint.to_account_info()
+    } else {
+        ctx.accounts.no_mint.to_account_info()
+    };
+    require!(
+        ctx.accounts.trader_share_account.mint == share_mint.key(),
+        PredictionMarketError::InvalidShareMint
+    );
+
+    // Quote the cost function difference, rounded down in the pool's favor.
+    // The pool only buys back shares it has sold.
+    let pool = &mut ctx.accounts.lmsr_pool;
+    let cost_before = pool.cost()?;
+    if outcome == Outcome::Yes {
+        pool.q_yes = pool
+            .q_yes
+            .checked_sub(shares)
+            .ok_or(PredictionMarketError::InsufficientPosition)?;
+    } else {
+        pool.q_no = pool
+            .q_no
+            .checked_sub(shares)
+            .ok_or(PredictionMarketError::InsufficientPosition)?;
+    }
+    let proceeds = cost_before
+        .checked_sub(pool.cost()?)
+        .ok_or(PredictionMarketError::Overflow)?
+        .saturating_sub(1);
+    require!(
+        proceeds >= min_proceeds,
+        PredictionMarketError::TradeLimitExceeded
+    );
+
+    let market = &mut ctx.accounts.market;
+    market.amm_collateral = market
+        .amm_collateral
+        .checked_sub(proceeds)
+        .ok_or(PredictionMarketError::AmmInsolvent)?;
+    require!(
+        market.amm_collateral >= pool.q_yes.max(pool.q_no),
+        PredictionMarketError::AmmInsolvent
+    );
+
+    // Burn the shares
+    let cpi_accounts = Burn {
+        mint: share_mint,
+        from: ctx.accounts.trader_share_account.to_account_info(),
+        authority: ctx.accounts.trader.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    burn(cpi_ctx, shares)?;
+
+    // Pay the trader
+    if proceeds > 0 {
+        let market_id_bytes = market_id.to_le_bytes();
+        let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: ctx.accounts.trader_token_account.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, proceeds)?;
+    }
+
+    let yes_price_bps = pool.yes_price_bps()?;
+    emit!(LmsrTrade {
+        market_id,
+        trader: ctx.accounts.trader.key(),
+        outcome,
+        is_buy: false,
+        shares,
+        amount: proceeds,
+        yes_price_bps,
+    });
+
+    msg!("LMSR shares sold");
+    msg!("Market ID: {}", market_id);
+    msg!("Outcome: {:?}", outcome);
+    msg!("Shares: {}", shares);
+    msg!("Proceeds: {}", proceeds);
+    msg!("YES Price (bps): {}", yes_price_bps);
+
+    Ok(())
+}
+
+pub fn withdraw_lmsr_subsidy_handler(
+    ctx: Context<WithdrawLmsrSubsidy>,
+    market_id: u64,
+) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let pool = &ctx.accounts.lmsr_pool;
+
+    let clock = Clock::get()?;
+    require_phase(
+        market,
+        clock.unix_timestamp,
+        &[MarketState::Resolved, MarketState::Settled, MarketState::Cancelled],
+    )?;
+    require_dispute_window_passed(market, clock.unix_timestamp)?;
+
+    // Winning shares are paid 1:1, so everything above the winning supply is surplus.
+    // A cancelled market refunds shares from trader collateral only.
+    let amount = if market.state == MarketState::Resolved {
+        let winning_supply = if market.winning_outcome == Outcome::Yes {
+            ctx.accounts.yes_mint.supply
+        } else {
+            ctx.accounts.no_mint.supply
+        };
+        market
+            .share_pot_remaining()?
+            .checked_sub(winning_supply)
+            .ok_or(PredictionMarketError::AmmInsolvent)?
+    } else {
+        pool.subsidy.min(market.share_pot_remaining()?)
+    };
+
+    if amount > 0 {
+        let market_id_bytes = market_id.to_le_bytes();
+        let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: ctx.accounts.creator_token_account.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+    }
+
+    ctx.accounts.lmsr_pool.subsidy_withdrawn = true;
+    let market = &mut ctx.accounts.market;
+    market.share_payouts = market
+        .share_payouts
+        .checked_add(amount)
+        .ok_or(PredictionMarketError::Overflow)?;
+    market.total_paid_out = market
+        .total_paid_out
+        .checked_add(amount)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    msg!("LMSR subsidy withdrawn");
+    msg!("Market ID: {}", market_id);
+    msg!("Amount: {}", amount);
+
+    Ok(())
+}
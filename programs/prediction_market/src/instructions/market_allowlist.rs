@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::error::PredictionMarketError;
+use crate::lifecycle::require_phase;
+use crate::state::{Market, MarketState};
+
+/// Attach, rotate or remove a market's participant allowlist (creator only). Only
+/// possible before the betting cutoff; positions taken earlier are unaffected.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SetAllowlistRoot<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(
+    ctx: Context<SetAllowlistRoot>,
+    market_id: u64,
+    root: Option<[u8; 32]>,
+) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let clock = Clock::get()?;
+    require_phase(market, clock.unix_timestamp, &[MarketState::Active])?;
+
+    market.allowlist_root = root;
+
+    msg!("Market allowlist updated");
+    msg!("Market ID: {}", market_id);
+    msg!("Root: {:?}", root);
+
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token::TokenAccount;
+
+use crate::error::PredictionMarketError;
+use crate::state::{Config, Market, MarketMode, MarketState, UserPosition};
+
+/// Migrate the config account to the current layout (admin only)
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: May predate the current layout, so it is deserialized in the handler
+    /// after being reallocated. Admin is checked there.
+    #[account(
+        mut,
+        seeds = [Config::SEED],
+        bump
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Migrate a market account to the current layout. Permissionless: the payer only funds rent.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct MigrateMarket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: May predate the current layout, so it is deserialized in the handler
+    /// after being reallocated.
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Migrate the signer's position in a market to the current layout
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct MigratePosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: May predate the current layout, so it is deserialized in the handler
+    /// after being reallocated.
+    #[account(
+        mut,
+        seeds = [UserPosition::SEED, market_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_position: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_config_handler(ctx: Context<MigrateConfig>) -> Result<()> {
+    let admin = ctx.accounts.admin.key();
+    let config = migrate_account::<Config>(
+        &ctx.accounts.config,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+        8 + Config::INIT_SPACE,
+        |config| {
+            require!(config.admin == admin, PredictionMarketError::InvalidAdmin);
+            require!(
+                config.version < Config::CURRENT_VERSION,
+                PredictionMarketError::AlreadyMigrated
+            );
+            config.version = Config::CURRENT_VERSION;
+            Ok(())
+        },
+    )?;
+
+    msg!("Config migrated");
+    msg!("Version: {}", config.version);
+
+    Ok(())
+}
+
+pub fn migrate_market_handler(ctx: Context<MigrateMarket>, market_id: u64) -> Result<()> {
+    let vault_balance = ctx.accounts.market_vault.amount;
+    let market = migrate_account::<Market>(
+        &ctx.accounts.market,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        8 + Market::INIT_SPACE,
+        |market| {
+            require!(
+                market.version < Market::CURRENT_VERSION,
+                PredictionMarketError::AlreadyMigrated
+            );
+            upgrade_market(market, vault_balance)?;
+            Ok(())
+        },
+    )?;
+
+    msg!("Market migrated");
+    msg!("Market ID: {}", market_id);
+    msg!("Version: {}", market.version);
+
+    Ok(())
+}
+
+/// Bring an older market's fields up to the current layout version.
+///
+/// Counters carved out of the padding after a market was created read as zero. Open
+/// markets have paid nothing out yet, so whatever the vault holds beyond the pools was
+/// committed through stake_and_commit; that total is backfilled so settlement sees the
+/// commits. Per-user counts cannot be recovered, so legacy markets fall back to totals
+/// (see `Market::has_counterparty`) and can only be closed once swept. Markets resolved
+/// before the dispute window existed have already paid out, so they keep paying out and
+/// can no longer be disputed; one under dispute uses up its single dispute.
+fn upgrade_market(market: &mut Market, vault_balance: u64) -> Result<()> {
+    // Markets from before modes may hold bets and commits side by side, so they keep
+    // both settlement paths
+    if market.version < 3 {
+        market.mode = MarketMode::Legacy;
+    }
+    if market.mode == MarketMode::Legacy
+        && market.state == MarketState::Active
+        && market.total_committed == 0
+    {
+        let pools = market
+            .yes_pool
+            .checked_add(market.no_pool)
+            .ok_or(PredictionMarketError::Overflow)?;
+        market.total_committed = vault_balance.saturating_sub(pools);
+    }
+    if market.version < 4 && market.state == MarketState::Disputed {
+        market.was_disputed = true;
+    }
+    market.version = Market::CURRENT_VERSION;
+    Ok(())
+}
+
+pub fn migrate_position_handler(ctx: Context<MigratePosition>, market_id: u64) -> Result<()> {
+    let position = migrate_account::<UserPosition>(
+        &ctx.accounts.user_position,
+        &ctx.accounts.user,
+        &ctx.accounts.system_program,
+        8 + UserPosition::INIT_SPACE,
+        |position| {
+            require!(
+                position.version < UserPosition::CURRENT_VERSION,
+                PredictionMarketError::AlreadyMigrated
+            );
+            position.version = UserPosition::CURRENT_VERSION;
+            Ok(())
+        },
+    )?;
+
+    msg!("Position migrated");
+    msg!("Market ID: {}", market_id);
+    msg!("User: {}", position.user);
+    msg!("Version: {}", position.version);
+
+    Ok(())
+}
+
+/// Grow `account` to `new_len` (zero-filled, payer tops up rent), then deserialize it,
+/// apply `upgrade` and write it back.
+///
+/// New fields are only ever added in place of trailing zero padding, so an old account's
+/// data followed by zeros is a valid encoding of the new layout with defaulted fields.
+fn migrate_account<'info, T>(
+    account: &UncheckedAccount<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+    upgrade: impl FnOnce(&mut T) -> Result<()>,
+) -> Result<T>
+where
+    T: AccountSerialize + AccountDeserialize,
+{
+    require_keys_eq!(
+        *account.owner,
+        crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+
+    if account.data_len() < new_len {
+        let rent_exempt = Rent::get()?.minimum_balance(new_len);
+        let top_up = rent_exempt.saturating_sub(account.lamports());
+        if top_up > 0 {
+            let cpi_accounts = Transfer {
+                from: payer.to_account_info(),
+                to: account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, top_up)?;
+        }
+        account.resize(new_len)?;
+    }
+
+    let mut data = T::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    upgrade(&mut data)?;
+    data.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::test_utils::zeroed;
+
+    /// An open market from before modes holding pari-mutuel bets on both sides and 70 in
+    /// commits, with every counter carved out since still 0
+    fn mixed_legacy_market() -> Market {
+        let mut market: Market = zeroed(Market::INIT_SPACE);
+        market.version = 1;
+        market.yes_pool = 100;
+        market.no_pool = 50;
+        market
+    }
+
+    fn position(yes_bet: u64, committed_amount: u64) -> UserPosition {
+        let mut position: UserPosition = zeroed(UserPosition::INIT_SPACE);
+        position.yes_bet = yes_bet;
+        position.committed_amount = committed_amount;
+        position
+    }
+
+    #[test]
+    fn mixed_legacy_market_keeps_both_settlement_paths() {
+        let mut market = mixed_legacy_market();
+        upgrade_market(&mut market, 220).unwrap();
+        assert_eq!(market.mode, MarketMode::Legacy);
+        assert_eq!(market.version, Market::CURRENT_VERSION);
+        assert_eq!(market.total_committed, 70);
+
+        // On cancellation bettors and committers are both refunded through claim_winnings
+        market.state = MarketState::Cancelled;
+        assert_eq!(market.claimable_deposits(&position(100, 0)).unwrap(), (100, 0));
+        assert_eq!(market.claimable_deposits(&position(0, 40)).unwrap(), (0, 40));
+
+        // On resolution bets are claimed and commits go through reveal_and_claim
+        market.state = MarketState::Resolved;
+        assert_eq!(market.claimable_deposits(&position(100, 40)).unwrap(), (100, 0));
+    }
+
+    #[test]
+    fn legacy_market_with_only_commits_is_not_empty() {
+        let mut market: Market = zeroed(Market::INIT_SPACE);
+        market.version = 1;
+        upgrade_market(&mut market, 70).unwrap();
+
+        // The commits are backfilled as a total, with no count behind them, and still
+        // keep the creator from withdrawing the market
+        assert_eq!((market.total_committed, market.commit_count), (70, 0));
+        assert!(!market.is_untouched(0).unwrap());
+
+        let mut empty: Market = zeroed(Market::INIT_SPACE);
+        empty.version = 1;
+        upgrade_market(&mut empty, 0).unwrap();
+        assert!(empty.is_untouched(0).unwrap());
+    }
+
+    #[test]
+    fn legacy_counterparty_falls_back_to_totals() {
+        // Opposed bets and uncounted commits settle normally
+        let mut market = mixed_legacy_market();
+        upgrade_market(&mut market, 220).unwrap();
+        assert!(market.has_counterparty());
+
+        // A lone counted committer has nobody to win from, even with opposed bets
+        market.commit_count = 1;
+        assert!(!market.has_counterparty());
+
+        // Bets on one side only are refunded
+        let mut market = mixed_legacy_market();
+        market.no_pool = 0;
+        upgrade_market(&mut market, 100).unwrap();
+        assert!(!market.has_counterparty());
+    }
+
+    #[test]
+    fn settled_markets_are_not_backfilled() {
+        let mut market = mixed_legacy_market();
+        market.state = MarketState::Resolved;
+        upgrade_market(&mut market, 20).unwrap();
+        assert_eq!(market.total_committed, 0);
+    }
+
+    #[test]
+    fn every_version_before_modes_becomes_legacy() {
+        for version in 0..3 {
+            let mut market = mixed_legacy_market();
+            market.version = version;
+            market.mode = MarketMode::Private;
+            upgrade_market(&mut market, 220).unwrap();
+            assert_eq!(market.mode, MarketMode::Legacy);
+        }
+    }
+
+    #[test]
+    fn open_dispute_uses_up_the_single_dispute() {
+        let mut market = mixed_legacy_market();
+        market.version = 3;
+        market.state = MarketState::Disputed;
+        market.disputed_at = 1_000;
+        upgrade_market(&mut market, 150).unwrap();
+        assert!(market.was_disputed);
+
+        let mut market = mixed_legacy_market();
+        market.version = 3;
+        market.state = MarketState::Resolved;
+        upgrade_market(&mut market, 150).unwrap();
+        assert!(!market.was_disputed);
+        assert_eq!(market.dispute_deadline, 0);
+    }
+}
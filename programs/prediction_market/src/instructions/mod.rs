@@ -1,27 +1,75 @@
+pub mod admin_transfer;
+pub mod audit_market;
 pub mod cancel_market;
 pub mod claim_winnings;
+pub mod close_accounts;
+pub mod cpmm;
 pub mod create_market;
+pub mod creator_allowlist;
+pub mod creator_bond;
+pub mod credentials;
+pub mod deposit_limits;
+pub mod dispute;
+pub mod exit_position;
 pub mod forfeit_unrevealed;
+pub mod house;
 pub mod initialize;
+pub mod lmsr;
+pub mod market_allowlist;
+pub mod migrate;
+pub mod orders;
 pub mod pause;
 pub mod reveal_and_claim;
 pub mod place_bet;
 pub mod stake_and_commit;
+pub mod set_market_limits;
+pub mod sweep_market;
 pub mod resolve_market;
+pub mod shares;
 pub mod update_config;
 
 // Re-export everything - the `handler` functions conflict but we access them via full paths
 #[allow(ambiguous_glob_reexports)]
+pub use admin_transfer::*;
+#[allow(ambiguous_glob_reexports)]
+pub use audit_market::*;
+#[allow(ambiguous_glob_reexports)]
 pub use cancel_market::*;
 #[allow(ambiguous_glob_reexports)]
 pub use claim_winnings::*;
 #[allow(ambiguous_glob_reexports)]
+pub use close_accounts::*;
+#[allow(ambiguous_glob_reexports)]
+pub use cpmm::*;
+#[allow(ambiguous_glob_reexports)]
 pub use create_market::*;
 #[allow(ambiguous_glob_reexports)]
+pub use creator_allowlist::*;
+#[allow(ambiguous_glob_reexports)]
+pub use creator_bond::*;
+#[allow(ambiguous_glob_reexports)]
+pub use credentials::*;
+#[allow(ambiguous_glob_reexports)]
+pub use deposit_limits::*;
+#[allow(ambiguous_glob_reexports)]
+pub use dispute::*;
+#[allow(ambiguous_glob_reexports)]
+pub use exit_position::*;
+#[allow(ambiguous_glob_reexports)]
 pub use forfeit_unrevealed::*;
 #[allow(ambiguous_glob_reexports)]
+pub use house::*;
+#[allow(ambiguous_glob_reexports)]
 pub use initialize::*;
 #[allow(ambiguous_glob_reexports)]
+pub use lmsr::*;
+#[allow(ambiguous_glob_reexports)]
+pub use market_allowlist::*;
+#[allow(ambiguous_glob_reexports)]
+pub use migrate::*;
+#[allow(ambiguous_glob_reexports)]
+pub use orders::*;
+#[allow(ambiguous_glob_reexports)]
 pub use pause::*;
 #[allow(ambiguous_glob_reexports)]
 pub use reveal_and_claim::*;
@@ -30,6 +78,12 @@ pub use place_bet::*;
 #[allow(ambiguous_glob_reexports)]
 pub use stake_and_commit::*;
 #[allow(ambiguous_glob_reexports)]
+pub use set_market_limits::*;
+#[allow(ambiguous_glob_reexports)]
+pub use sweep_market::*;
+#[allow(ambiguous_glob_reexports)]
 pub use resolve_market::*;
 #[allow(ambiguous_glob_reexports)]
+pub use shares::*;
+#[allow(ambiguous_glob_reexports)]
 pub use update_config::*;
@@ -0,0 +1,821 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::{BPS_DENOMINATOR, MIN_ORDER_SHARES, MIN_ORDER_VALUE};
+use crate::error::PredictionMarketError;
+use crate::lifecycle::require_phase;
+use crate::events::OrderFilled;
+use crate::state::{
+    enforce_user_limits, Config, Credential, Market, MarketMode, MarketState, Order, OrderBook,
+    Side, UserLimits, MAX_BOOK_ORDERS,
+};
+
+/// Open a limit order book for a market's YES shares. Permissionless; the payer funds the
+/// book account and its two escrow accounts.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct InitOrderBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_BET) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = market.mode == MarketMode::Public @ PredictionMarketError::WrongMarketMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + OrderBook::INIT_SPACE,
+        seeds = [OrderBook::SEED, market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        constraint = token_mint.key() == config.token_mint
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    /// Collateral escrowed by resting bids and owed to traders
+    #[account(
+        init,
+        payer = payer,
+        seeds = [OrderBook::COLLATERAL_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = market
+    )]
+    pub collateral_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// YES shares escrowed by resting asks and owed to traders
+    #[account(
+        init,
+        payer = payer,
+        seeds = [OrderBook::YES_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump,
+        token::mint = yes_mint,
+        token::authority = market
+    )]
+    pub yes_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Place a resting limit order. Bids escrow collateral at the limit price, asks escrow
+/// YES shares. Orders are matched by the match_orders crank.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct PlaceOrder<'info> {
+    pub trader: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_BET) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::COLLATERAL_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.collateral_escrow_bump
+    )]
+    pub collateral_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::YES_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.yes_escrow_bump
+    )]
+    pub yes_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's collateral token account (funds bids)
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = trader
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's YES share account (funds asks)
+    #[account(
+        mut,
+        token::mint = yes_escrow.mint,
+        token::authority = trader
+    )]
+    pub trader_yes_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Trader's deposit limits; may not exist. Seeds are checked here and the
+    /// account is deserialized in the handler when it does.
+    #[account(
+        mut,
+        seeds = [UserLimits::SEED, trader.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+
+    /// Trader's credential (markets with a minimum tier only)
+    #[account(
+        seeds = [Credential::SEED, trader.key().as_ref()],
+        bump = credential.bump
+    )]
+    pub credential: Option<Box<Account<'info, Credential>>>,
+
+    /// Owner's token account for the order a full book evicts: collateral for a bid,
+    /// YES shares for an ask (only needed when the book is full)
+    #[account(mut)]
+    pub evicted_owner_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Cancel a resting order, paying its remaining escrow straight back to the trader.
+/// Allowed in any market state.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct CancelOrder<'info> {
+    pub trader: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::COLLATERAL_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.collateral_escrow_bump
+    )]
+    pub collateral_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::YES_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.yes_escrow_bump
+    )]
+    pub yes_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's collateral token account (refunds bids)
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = trader
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's YES share account (refunds asks)
+    #[account(
+        mut,
+        token::mint = yes_escrow.mint,
+        token::authority = trader
+    )]
+    pub trader_yes_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Match crossing orders in price-time priority. Permissionless crank.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct MatchOrders<'info> {
+    pub cranker: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_BET) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+}
+
+/// Withdraw a trader's free collateral and YES shares from the book's escrow. Shares
+/// bought on the book settle like any other through redeem_shares.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SettleFunds<'info> {
+    pub trader: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::COLLATERAL_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.collateral_escrow_bump
+    )]
+    pub collateral_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::YES_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.yes_escrow_bump
+    )]
+    pub yes_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = trader
+    )]
+    pub trader_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's YES share account
+    #[account(
+        mut,
+        token::mint = yes_escrow.mint,
+        token::authority = trader
+    )]
+    pub trader_yes_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Settle a trader's book balance to their associated token accounts once the market is
+/// finalized, cancelling any orders they still have resting. Permissionless, so no trader
+/// can keep the market from closing; the caller pays for missing token accounts.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SettleFundsFor<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::COLLATERAL_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.collateral_escrow_bump
+    )]
+    pub collateral_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [OrderBook::YES_ESCROW_SEED, market_id.to_le_bytes().as_ref()],
+        bump = order_book.yes_escrow_bump
+    )]
+    pub yes_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Trader whose balance is settled; only used as the token account owner
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = token_mint.key() == config.token_mint
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    /// Trader's associated collateral token account
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Trader's associated YES share account
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = yes_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_yes_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// `price_bps * quantity / BPS_DENOMINATOR` in collateral units, rounded down or up
+fn order_value(price_bps: u16, quantity: u64, round_up: bool) -> Result<u64> {
+    let notional = (price_bps as u128)
+        .checked_mul(quantity as u128)
+        .ok_or(PredictionMarketError::Overflow)?;
+    let value = if round_up {
+        notional.div_ceil(BPS_DENOMINATOR as u128)
+    } else {
+        notional / BPS_DENOMINATOR as u128
+    };
+    u64::try_from(value).map_err(|_| PredictionMarketError::Overflow.into())
+}
+
+/// Pay `amount` out of one of the book's escrow accounts, signed by the market
+fn pay_from_escrow<'info>(
+    market: &Account<'info, Market>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    amount: u64,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let market_id_bytes = market.id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority: market.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    transfer(cpi_ctx, amount)
+}
+
+pub fn init_order_book_handler(ctx: Context<InitOrderBook>, market_id: u64) -> Result<()> {
+    let book = &mut ctx.accounts.order_book;
+    book.market_id = market_id;
+    book.next_order_id = 0;
+    book.orders = Vec::new();
+    book.balances = Vec::new();
+    book.collateral_escrow_bump = ctx.bumps.collateral_escrow;
+    book.yes_escrow_bump = ctx.bumps.yes_escrow;
+    book.bump = ctx.bumps.order_book;
+
+    msg!("Order book opened");
+    msg!("Market ID: {}", market_id);
+
+    Ok(())
+}
+
+pub fn place_order_handler(
+    ctx: Context<PlaceOrder>,
+    market_id: u64,
+    side: Side,
+    price_bps: u16,
+    quantity: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(quantity > 0, PredictionMarketError::ZeroAmount);
+    let one_unit = 10u64
+        .checked_pow(ctx.accounts.config.token_decimals as u32)
+        .ok_or(PredictionMarketError::Overflow)?;
+    let min_quantity = one_unit
+        .checked_mul(MIN_ORDER_SHARES)
+        .ok_or(PredictionMarketError::Overflow)?;
+    require!(quantity >= min_quantity, PredictionMarketError::OrderTooSmall);
+    require!(
+        price_bps > 0 && (price_bps as u64) < BPS_DENOMINATOR,
+        PredictionMarketError::InvalidOrderPrice
+    );
+    // The order must also be worth something, or orders at a near-zero price could take
+    // up the book's trader slots for free
+    let min_value = one_unit
+        .checked_mul(MIN_ORDER_VALUE)
+        .ok_or(PredictionMarketError::Overflow)?;
+    require!(
+        order_value(price_bps, quantity, false)? >= min_value,
+        PredictionMarketError::OrderTooSmall
+    );
+    let clock = Clock::get()?;
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
+    ctx.accounts
+        .market
+        .check_allowlisted(&ctx.accounts.trader.key(), &allowlist_proof)?;
+    ctx.accounts.market.check_credential(
+        ctx.accounts.credential.as_deref().map(|credential| &**credential),
+        &ctx.accounts.config.attester,
+        clock.unix_timestamp,
+    )?;
+
+    // A full book makes room by evicting its least competitive order, if the new one is
+    // more competitive. The evicted escrow goes straight back to its owner, so their
+    // balance entry does not outlive their orders.
+    let mut evicted = None;
+    if ctx.accounts.order_book.orders.len() >= MAX_BOOK_ORDERS {
+        let book = &mut ctx.accounts.order_book;
+        let worst = book
+            .worst_order()
+            .ok_or(PredictionMarketError::OrderBookFull)?;
+        require!(
+            side.price_priority(price_bps) > book.orders[worst].price_priority(),
+            PredictionMarketError::OrderBookFull
+        );
+        let order = book.take_order(worst)?;
+
+        let escrow_account = match order.side {
+            Side::Bid => &ctx.accounts.collateral_escrow,
+            Side::Ask => &ctx.accounts.yes_escrow,
+        };
+        let owner_account = ctx
+            .accounts
+            .evicted_owner_account
+            .as_ref()
+            .filter(|account| account.owner == order.owner && account.mint == escrow_account.mint)
+            .ok_or(PredictionMarketError::EvictedOwnerAccountMismatch)?;
+        pay_from_escrow(
+            &ctx.accounts.market,
+            escrow_account,
+            owner_account,
+            order.escrow,
+            &ctx.accounts.token_program,
+        )?;
+        if ctx.accounts.order_book.remove_balance_if_empty(&order.owner) {
+            let market = &mut ctx.accounts.market;
+            market.book_traders = market.book_traders.saturating_sub(1);
+        }
+        evicted = Some(order.id);
+    }
+
+    // Escrow the order
+    let (escrow, from, to) = match side {
+        Side::Bid => (
+            order_value(price_bps, quantity, true)?,
+            ctx.accounts.trader_token_account.to_account_info(),
+            ctx.accounts.collateral_escrow.to_account_info(),
+        ),
+        Side::Ask => (
+            quantity,
+            ctx.accounts.trader_yes_account.to_account_info(),
+            ctx.accounts.yes_escrow.to_account_info(),
+        ),
+    };
+    if side == Side::Bid {
+        enforce_user_limits(&ctx.accounts.user_limits, escrow)?;
+    }
+    let cpi_accounts = Transfer {
+        from,
+        to,
+        authority: ctx.accounts.trader.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer(cpi_ctx, escrow)?;
+
+    let trader = ctx.accounts.trader.key();
+    let book = &mut ctx.accounts.order_book;
+    let (balance, created) = book.balance_entry(trader)?;
+    balance.open_orders = balance
+        .open_orders
+        .checked_add(1)
+        .ok_or(PredictionMarketError::Overflow)?;
+    if created {
+        let market = &mut ctx.accounts.market;
+        market.book_traders = market
+            .book_traders
+            .checked_add(1)
+            .ok_or(PredictionMarketError::Overflow)?;
+    }
+
+    let order_id = book.next_order_id;
+    book.next_order_id = order_id
+        .checked_add(1)
+        .ok_or(PredictionMarketError::Overflow)?;
+    book.orders.push(Order {
+        id: order_id,
+        owner: trader,
+        side,
+        price_bps,
+        quantity,
+        escrow,
+    });
+
+    msg!("Order placed");
+    msg!("Market ID: {}", market_id);
+    msg!("Order ID: {}", order_id);
+    msg!("Trader: {}", trader);
+    msg!("Side: {:?}", side);
+    msg!("Price BPS: {}", price_bps);
+    msg!("Quantity: {}", quantity);
+    msg!("Escrow: {}", escrow);
+    if let Some(evicted) = evicted {
+        msg!("Evicted Order ID: {}", evicted);
+    }
+
+    Ok(())
+}
+
+pub fn cancel_order_handler(ctx: Context<CancelOrder>, market_id: u64, order_id: u64) -> Result<()> {
+    let book = &mut ctx.accounts.order_book;
+    let index = book
+        .orders
+        .iter()
+        .position(|o| o.id == order_id)
+        .ok_or(PredictionMarketError::OrderNotFound)?;
+    let order = book.orders[index];
+    require!(
+        order.owner == ctx.accounts.trader.key(),
+        PredictionMarketError::NotOrderOwner
+    );
+    book.take_order(index)?;
+
+    // Refund the escrow right away so an idle trader does not hold one of the book's
+    // trader slots
+    let (from, to) = match order.side {
+        Side::Bid => (&ctx.accounts.collateral_escrow, &ctx.accounts.trader_token_account),
+        Side::Ask => (&ctx.accounts.yes_escrow, &ctx.accounts.trader_yes_account),
+    };
+    pay_from_escrow(
+        &ctx.accounts.market,
+        from,
+        to,
+        order.escrow,
+        &ctx.accounts.token_program,
+    )?;
+    if ctx.accounts.order_book.remove_balance_if_empty(&order.owner) {
+        let market = &mut ctx.accounts.market;
+        market.book_traders = market.book_traders.saturating_sub(1);
+    }
+
+    msg!("Order cancelled");
+    msg!("Market ID: {}", market_id);
+    msg!("Order ID: {}", order_id);
+    msg!("Released: {}", order.escrow);
+
+    Ok(())
+}
+
+pub fn match_orders_handler(ctx: Context<MatchOrders>, market_id: u64, limit: u8) -> Result<()> {
+    let clock = Clock::get()?;
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
+
+    let book = &mut ctx.accounts.order_book;
+    let mut fills = 0u8;
+    while fills < limit {
+        let (bid_index, ask_index) = match (book.best_bid(), book.best_ask()) {
+            (Some(bid_index), Some(ask_index)) => (bid_index, ask_index),
+            _ => break,
+        };
+        let mut bid = book.orders[bid_index];
+        let mut ask = book.orders[ask_index];
+        if bid.price_bps < ask.price_bps {
+            break;
+        }
+
+        // The resting (older) order sets the price
+        let price_bps = if bid.id < ask.id {
+            bid.price_bps
+        } else {
+            ask.price_bps
+        };
+        let quantity = bid.quantity.min(ask.quantity);
+        let cost = order_value(price_bps, quantity, false)?;
+        // A partial fill releases the bid's escrow rounded down, so what stays behind
+        // always covers the rest of the order; a full fill releases all of it
+        let released = if quantity == bid.quantity {
+            bid.escrow
+        } else {
+            order_value(bid.price_bps, quantity, false)?
+        };
+        let refund = released
+            .checked_sub(cost)
+            .ok_or(PredictionMarketError::AccountingMismatch)?;
+
+        bid.quantity -= quantity;
+        bid.escrow = bid
+            .escrow
+            .checked_sub(released)
+            .ok_or(PredictionMarketError::AccountingMismatch)?;
+        ask.quantity -= quantity;
+        ask.escrow = ask
+            .escrow
+            .checked_sub(quantity)
+            .ok_or(PredictionMarketError::AccountingMismatch)?;
+        book.orders[bid_index] = bid;
+        book.orders[ask_index] = ask;
+
+        let buyer = book.balance_mut(&bid.owner)?;
+        buyer.yes_free = buyer
+            .yes_free
+            .checked_add(quantity)
+            .ok_or(PredictionMarketError::Overflow)?;
+        buyer.collateral_free = buyer
+            .collateral_free
+            .checked_add(refund)
+            .ok_or(PredictionMarketError::Overflow)?;
+        if bid.quantity == 0 {
+            buyer.open_orders = buyer.open_orders.saturating_sub(1);
+        }
+        let seller = book.balance_mut(&ask.owner)?;
+        seller.collateral_free = seller
+            .collateral_free
+            .checked_add(cost)
+            .ok_or(PredictionMarketError::Overflow)?;
+        if ask.quantity == 0 {
+            seller.open_orders = seller.open_orders.saturating_sub(1);
+        }
+
+        // Remove filled orders, higher index first so the other index stays valid.
+        // Leftover escrow on a filled ask is impossible; on a filled bid it was refunded.
+        let mut filled: Vec<usize> = [(bid_index, bid.quantity), (ask_index, ask.quantity)]
+            .into_iter()
+            .filter(|(_, remaining)| *remaining == 0)
+            .map(|(index, _)| index)
+            .collect();
+        filled.sort_unstable_by(|a, b| b.cmp(a));
+        for index in filled {
+            book.orders.swap_remove(index);
+        }
+
+        emit!(OrderFilled {
+            market_id,
+            bid_order_id: bid.id,
+            ask_order_id: ask.id,
+            buyer: bid.owner,
+            seller: ask.owner,
+            price_bps,
+            quantity,
+            bid_remaining: bid.quantity,
+            ask_remaining: ask.quantity,
+        });
+        fills += 1;
+    }
+
+    msg!("Orders matched");
+    msg!("Market ID: {}", market_id);
+    msg!("Fills: {}", fills);
+    msg!("Resting Orders: {}", book.orders.len());
+
+    Ok(())
+}
+
+/// Pay out `owner`'s free collateral and YES shares from the book's escrow, dropping the
+/// balance entry, and the market's count of book traders, once nothing is left in it
+fn withdraw_balance<'info>(
+    market: &mut Account<'info, Market>,
+    book: &mut OrderBook,
+    owner: &Pubkey,
+    escrows: [&Account<'info, TokenAccount>; 2],
+    destinations: [&Account<'info, TokenAccount>; 2],
+    token_program: &Program<'info, Token>,
+) -> Result<(u64, u64)> {
+    let balance = book.balance_mut(owner)?;
+    let collateral = balance.collateral_free;
+    let shares = balance.yes_free;
+    balance.collateral_free = 0;
+    balance.yes_free = 0;
+
+    for ((from, to), amount) in escrows.into_iter().zip(destinations).zip([collateral, shares]) {
+        pay_from_escrow(market, from, to, amount, token_program)?;
+    }
+
+    if book.remove_balance_if_empty(owner) {
+        market.book_traders = market.book_traders.saturating_sub(1);
+    }
+    Ok((collateral, shares))
+}
+
+pub fn settle_funds_handler(ctx: Context<SettleFunds>, market_id: u64) -> Result<()> {
+    let trader = ctx.accounts.trader.key();
+    let (collateral, shares) = withdraw_balance(
+        &mut ctx.accounts.market,
+        &mut ctx.accounts.order_book,
+        &trader,
+        [&ctx.accounts.collateral_escrow, &ctx.accounts.yes_escrow],
+        [&ctx.accounts.trader_token_account, &ctx.accounts.trader_yes_account],
+        &ctx.accounts.token_program,
+    )?;
+
+    msg!("Funds settled");
+    msg!("Market ID: {}", market_id);
+    msg!("Trader: {}", trader);
+    msg!("Collateral: {}", collateral);
+    msg!("YES Shares: {}", shares);
+
+    Ok(())
+}
+
+pub fn settle_funds_for_handler(ctx: Context<SettleFundsFor>, market_id: u64) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+
+    // Orders still resting can no longer fill, so release their escrow first
+    let book = &mut ctx.accounts.order_book;
+    let mut cancelled = 0u32;
+    while let Some(index) = book.orders.iter().position(|o| o.owner == owner) {
+        book.remove_order(index)?;
+        cancelled += 1;
+    }
+
+    let (collateral, shares) = withdraw_balance(
+        &mut ctx.accounts.market,
+        &mut ctx.accounts.order_book,
+        &owner,
+        [&ctx.accounts.collateral_escrow, &ctx.accounts.yes_escrow],
+        [&ctx.accounts.owner_token_account, &ctx.accounts.owner_yes_account],
+        &ctx.accounts.token_program,
+    )?;
+
+    msg!("Funds settled for trader");
+    msg!("Market ID: {}", market_id);
+    msg!("Trader: {}", owner);
+    msg!("Orders Cancelled: {}", cancelled);
+    msg!("Collateral: {}", collateral);
+    msg!("YES Shares: {}", shares);
+
+    Ok(())
+}
@@ -1,14 +1,19 @@
 use anchor_lang::prelude::*;
 
 use crate::error::PredictionMarketError;
-use crate::state::Config;
+use crate::state::{Config, Market, Role};
 
+/// Circuit breaker controls.
+///
+/// Protocol-wide pauses are a bitmask of `Config::PAUSE_*` flags, so individual actions can
+/// be halted (e.g. `Config::PAUSE_WITHDRAW_ONLY` stops new stakes while settlement continues).
+/// A single market can additionally be frozen with its own pause flag.
 #[derive(Accounts)]
 pub struct Pause<'info> {
     #[account(
-        constraint = admin.key() == config.admin @ PredictionMarketError::InvalidAdmin
+        constraint = config.has_role(Role::Pauser, &authority.key()) @ PredictionMarketError::MissingRole
     )]
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
 
     #[account(
         mut,
@@ -18,20 +23,84 @@ pub struct Pause<'info> {
     pub config: Account<'info, Config>,
 }
 
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct PauseMarket<'info> {
+    #[account(
+        constraint = config.has_role(Role::Pauser, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+}
+
+/// Pause every action
 pub fn pause_handler(ctx: Context<Pause>) -> Result<()> {
-    require!(!ctx.accounts.config.paused, PredictionMarketError::Paused);
+    require!(
+        ctx.accounts.config.pause_flags != Config::PAUSE_ALL,
+        PredictionMarketError::Paused
+    );
 
-    ctx.accounts.config.paused = true;
+    ctx.accounts.config.pause_flags = Config::PAUSE_ALL;
     msg!("Contract paused");
 
     Ok(())
 }
 
+/// Clear every pause flag
 pub fn unpause_handler(ctx: Context<Pause>) -> Result<()> {
-    require!(ctx.accounts.config.paused, PredictionMarketError::NotPaused);
+    require!(
+        ctx.accounts.config.pause_flags != 0,
+        PredictionMarketError::NotPaused
+    );
 
-    ctx.accounts.config.paused = false;
+    ctx.accounts.config.pause_flags = 0;
     msg!("Contract unpaused");
 
     Ok(())
 }
+
+/// Replace the pause bitmask
+pub fn set_pause_flags_handler(ctx: Context<Pause>, pause_flags: u8) -> Result<()> {
+    require!(
+        pause_flags & !Config::PAUSE_ALL == 0,
+        PredictionMarketError::InvalidPauseFlags
+    );
+
+    ctx.accounts.config.pause_flags = pause_flags;
+    msg!("Pause flags updated");
+    msg!("Pause Flags: {:#08b}", pause_flags);
+
+    Ok(())
+}
+
+/// Freeze or unfreeze a single market
+pub fn set_market_paused_handler(
+    ctx: Context<PauseMarket>,
+    market_id: u64,
+    paused: bool,
+) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    if paused {
+        require!(!market.paused, PredictionMarketError::MarketPaused);
+    } else {
+        require!(market.paused, PredictionMarketError::NotPaused);
+    }
+
+    market.paused = paused;
+    msg!(if paused { "Market paused" } else { "Market unpaused" });
+    msg!("Market ID: {}", market_id);
+
+    Ok(())
+}
@@ -1,9 +1,20 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+use anchor_spl::token::{mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer};
 
+use crate::constants::BPS_DENOMINATOR;
 use crate::error::PredictionMarketError;
-use crate::state::{Config, Market, MarketState, Outcome, UserPosition};
-
+use crate::lifecycle::require_phase;
+use crate::state::{
+    enforce_user_limits, Config, Credential, Market, MarketMode, MarketState, MarketType, Outcome,
+    UserLimits, UserStake,
+};
+
+/// Bet on an outcome of a pari-mutuel market.
+///
+/// The stake is added to the chosen side's pool and the bettor receives the same amount
+/// of that side's outcome shares, which are redeemed through redeem_shares once the
+/// market settles. Invite-only markets require a Merkle proof that the bettor is on the
+/// allowlist.
 #[derive(Accounts)]
 #[instruction(market_id: u64)]
 pub struct PlaceBet<'info> {
@@ -13,7 +24,7 @@ pub struct PlaceBet<'info> {
     #[account(
         seeds = [Config::SEED],
         bump = config.bump,
-        constraint = !config.paused @ PredictionMarketError::Paused
+        constraint = !config.is_paused(Config::PAUSE_BET) @ PredictionMarketError::Paused
     )]
     pub config: Account<'info, Config>,
 
@@ -21,7 +32,10 @@ pub struct PlaceBet<'info> {
         mut,
         seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
         bump = market.bump,
-        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.market_type == MarketType::PariMutuel @ PredictionMarketError::WrongMarketType,
+        constraint = market.mode != MarketMode::Private @ PredictionMarketError::WrongMarketMode
     )]
     pub market: Account<'info, Market>,
 
@@ -33,13 +47,25 @@ pub struct PlaceBet<'info> {
     pub market_vault: Account<'info, TokenAccount>,
 
     #[account(
-        init_if_needed,
-        payer = bettor,
-        space = 8 + UserPosition::INIT_SPACE,
-        seeds = [UserPosition::SEED, market_id.to_le_bytes().as_ref(), bettor.key().as_ref()],
-        bump
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// Bettor's token account for the chosen outcome's shares
+    #[account(
+        mut,
+        token::authority = bettor
     )]
-    pub user_position: Account<'info, UserPosition>,
+    pub bettor_share_account: Box<Account<'info, TokenAccount>>,
 
     /// Bettor's token account
     #[account(
@@ -49,6 +75,32 @@ pub struct PlaceBet<'info> {
     )]
     pub bettor_token_account: Account<'info, TokenAccount>,
 
+    /// Running deposit total for stake limits
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + UserStake::INIT_SPACE,
+        seeds = [UserStake::SEED, market_id.to_le_bytes().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    /// CHECK: Bettor's deposit limits; may not exist. Seeds are checked here and the
+    /// account is deserialized in the handler when it does.
+    #[account(
+        mut,
+        seeds = [UserLimits::SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+
+    /// Bettor's credential (markets with a minimum tier only)
+    #[account(
+        seeds = [Credential::SEED, bettor.key().as_ref()],
+        bump = credential.bump
+    )]
+    pub credential: Option<Box<Account<'info, Credential>>>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
@@ -58,6 +110,8 @@ pub fn handler(
     market_id: u64,
     outcome: Outcome,
     amount: u64,
+    min_payout_multiplier_bps: u64,
+    allowlist_proof: Vec<[u8; 32]>,
 ) -> Result<()> {
     // Validations
     require!(amount > 0, PredictionMarketError::ZeroAmount);
@@ -67,11 +121,43 @@ pub fn handler(
     );
 
     let clock = Clock::get()?;
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
+
+    let share_mint = if outcome == Outcome::Yes {
+        ctx.accounts.yes_mint.to_account_info()
+    } else {
+        ctx.accounts.no_mint.to_account_info()
+    };
     require!(
-        clock.unix_timestamp < ctx.accounts.market.resolution_time,
-        PredictionMarketError::MarketExpired
+        ctx.accounts.bettor_share_account.mint == share_mint.key(),
+        PredictionMarketError::InvalidShareMint
     );
 
+    ctx.accounts
+        .market
+        .check_allowlisted(&ctx.accounts.bettor.key(), &allowlist_proof)?;
+    ctx.accounts.market.check_credential(
+        ctx.accounts.credential.as_deref().map(|credential| &**credential),
+        &ctx.accounts.config.attester,
+        clock.unix_timestamp,
+    )?;
+
+    // Stake limits: the user's own, then the market's and the protocol's
+    enforce_user_limits(&ctx.accounts.user_limits, amount)?;
+    let user_stake = &mut ctx.accounts.user_stake;
+    ctx.accounts.market.check_stake_limits(
+        amount,
+        user_stake.total_staked,
+        ctx.accounts.config.max_stake_per_wallet,
+    )?;
+    user_stake.market_id = market_id;
+    user_stake.user = ctx.accounts.bettor.key();
+    user_stake.total_staked = user_stake
+        .total_staked
+        .checked_add(amount)
+        .ok_or(PredictionMarketError::Overflow)?;
+    user_stake.bump = ctx.bumps.user_stake;
+
     // Transfer tokens to vault
     let cpi_accounts = Transfer {
         from: ctx.accounts.bettor_token_account.to_account_info(),
@@ -82,50 +168,74 @@ pub fn handler(
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
     transfer(cpi_ctx, amount)?;
 
-    // Update market pools
+    // Update market pools and bet shares
     let market = &mut ctx.accounts.market;
     if outcome == Outcome::Yes {
         market.yes_pool = market
             .yes_pool
             .checked_add(amount)
             .ok_or(PredictionMarketError::Overflow)?;
+        market.yes_bet_shares = market
+            .yes_bet_shares
+            .checked_add(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
     } else {
         market.no_pool = market
             .no_pool
             .checked_add(amount)
             .ok_or(PredictionMarketError::Overflow)?;
-    }
-
-    // Update user position
-    let position = &mut ctx.accounts.user_position;
-    if position.market_id == 0 {
-        // New position
-        position.market_id = market_id;
-        position.user = ctx.accounts.bettor.key();
-        position.yes_bet = 0;
-        position.no_bet = 0;
-        position.claimed = false;
-        position.bump = ctx.bumps.user_position;
-    }
-
-    if outcome == Outcome::Yes {
-        position.yes_bet = position
-            .yes_bet
+        market.no_bet_shares = market
+            .no_bet_shares
             .checked_add(amount)
             .ok_or(PredictionMarketError::Overflow)?;
+    }
+
+    // Slippage guard: the post-bet payout multiplier on the chosen side, both pools plus
+    // exit fees / that side's pool in basis points, must not fall below the bettor's
+    // minimum. Zero disables the check.
+    let own_pool = if outcome == Outcome::Yes {
+        market.yes_pool
     } else {
-        position.no_bet = position
-            .no_bet
-            .checked_add(amount)
-            .ok_or(PredictionMarketError::Overflow)?;
+        market.no_pool
+    };
+    let total_pool = market
+        .yes_pool
+        .checked_add(market.no_pool)
+        .and_then(|pools| pools.checked_add(market.exit_fees))
+        .ok_or(PredictionMarketError::Overflow)?;
+    let payout_multiplier_bps = (total_pool as u128)
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .ok_or(PredictionMarketError::Overflow)?
+        .checked_div(own_pool as u128)
+        .ok_or(PredictionMarketError::Overflow)?;
+    if min_payout_multiplier_bps > 0 {
+        require!(
+            payout_multiplier_bps >= min_payout_multiplier_bps as u128,
+            PredictionMarketError::SlippageExceeded
+        );
     }
 
+    // Mint outcome shares 1:1 with the stake
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = MintTo {
+        mint: share_mint,
+        to: ctx.accounts.bettor_share_account.to_account_info(),
+        authority: market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    mint_to(cpi_ctx, amount)?;
+
     msg!("Bet placed");
     msg!("Market ID: {}", market_id);
     msg!("Outcome: {:?}", outcome);
     msg!("Amount: {}", amount);
     msg!("YES Pool: {}", market.yes_pool);
     msg!("NO Pool: {}", market.no_pool);
+    msg!("Payout multiplier (bps): {}", payout_multiplier_bps);
 
     Ok(())
 }
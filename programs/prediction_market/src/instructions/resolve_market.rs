@@ -3,19 +3,21 @@ use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
 use crate::constants::SOL_USD_FEED_ID;
 use crate::error::PredictionMarketError;
-use crate::state::{Config, Market, MarketState, Outcome};
+use crate::lifecycle::{require_phase, transition};
+use crate::state::{AllowedCreator, CancelReason, Config, Market, MarketState, Outcome, Role};
 
 #[derive(Accounts)]
 #[instruction(market_id: u64)]
 pub struct ResolveMarket<'info> {
     #[account(
-        constraint = admin.key() == config.admin @ PredictionMarketError::InvalidAdmin
+        constraint = config.has_role(Role::Resolver, &authority.key()) @ PredictionMarketError::MissingRole
     )]
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
 
     #[account(
         seeds = [Config::SEED],
-        bump = config.bump
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_RESOLVE) @ PredictionMarketError::Paused
     )]
     pub config: Account<'info, Config>,
 
@@ -23,10 +25,19 @@ pub struct ResolveMarket<'info> {
         mut,
         seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
         bump = market.bump,
-        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketAlreadyFinalized
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketAlreadyFinalized,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused
     )]
     pub market: Account<'info, Market>,
 
+    /// Creator's allowlist entry (required if the market holds an open market slot)
+    #[account(
+        mut,
+        seeds = [AllowedCreator::SEED, market.creator.as_ref()],
+        bump = allowed_creator.bump
+    )]
+    pub allowed_creator: Option<Account<'info, AllowedCreator>>,
+
     /// Pyth price update account for SOL/USD feed
     /// CHECK: Validated in handler - must be a valid PriceUpdateV2 for SOL/USD
     pub price_update: Account<'info, PriceUpdateV2>,
@@ -41,23 +52,32 @@ pub fn handler(
     let clock = Clock::get()?;
 
     // Validations
-    require!(
-        clock.unix_timestamp >= market.resolution_time,
-        PredictionMarketError::MarketNotExpired
-    );
-
-    // Check for opposition (only for legacy markets, commit-reveal doesn't use pools)
-    // This check can be removed or made optional for commit-reveal markets
-    // require!(
-    //     market.yes_pool > 0 && market.no_pool > 0,
-    //     PredictionMarketError::NoOpposition
-    // );
+    require_phase(market, clock.unix_timestamp, &[MarketState::BettingClosed])?;
+
+    // Free up the creator's open market slot
+    market.release_creator_slot(ctx.accounts.allowed_creator.as_deref_mut())?;
+
+    // Markets without opposition settle as a refund (same as a cancellation) so nobody
+    // loses money without a counterparty; claim_winnings refunds both bets and commits
+    if !market.has_counterparty() {
+        transition(market, MarketState::Cancelled, clock.unix_timestamp)?;
+        market.cancel_reason = CancelReason::NoCounterparty;
+
+        msg!("Market has no counterparty - settled as refund");
+        msg!("Market ID: {}", market.id);
+        msg!("YES Pool: {}", market.yes_pool);
+        msg!("NO Pool: {}", market.no_pool);
+        msg!("Committers: {}", market.commit_count);
+        msg!("Claim Expiry: {}", market.claim_expiry);
+
+        return Ok(());
+    }
 
     // All markets use Pyth oracle for SOL/USD price resolution
     let price_update = &ctx.accounts.price_update;
     
-    // Maximum age for price update (30 seconds)
-    let maximum_age: u64 = 30;
+    // Maximum age for price update (oracle policy from config)
+    let maximum_age: u64 = ctx.accounts.config.max_price_age;
     
     // Get price from Pyth price update account using hardcoded SOL/USD feed ID
     // This will fail if:
@@ -98,21 +118,16 @@ pub fn handler(
          if price.price >= threshold { ">=" } else { "<" }, 
          final_outcome);
 
-    // Resolve market
-    market.state = MarketState::Resolved;
+    // Resolve market; sets the reveal deadline (2 weeks after resolution time) and
+    // the claim expiry
+    transition(market, MarketState::Resolved, clock.unix_timestamp)?;
     market.winning_outcome = final_outcome;
-    
-    // Set reveal deadline: 2 weeks (14 days) after resolution time
-    // 14 days = 14 * 24 * 60 * 60 = 1,209,600 seconds
-    const TWO_WEEKS_SECONDS: i64 = 14 * 24 * 60 * 60;
-    market.reveal_deadline = market.resolution_time
-        .checked_add(TWO_WEEKS_SECONDS)
-        .ok_or(PredictionMarketError::Overflow)?;
 
     msg!("Market resolved");
     msg!("Market ID: {}", market.id);
     msg!("Winning Outcome: {:?}", final_outcome);
     msg!("Reveal Deadline: {}", market.reveal_deadline);
+    msg!("Claim Expiry: {}", market.claim_expiry);
     msg!("YES Pool: {}", market.yes_pool);
     msg!("NO Pool: {}", market.no_pool);
 
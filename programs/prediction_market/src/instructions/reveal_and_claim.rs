@@ -4,9 +4,17 @@ use sha2::{Sha256, Digest};
 
 
 use crate::error::PredictionMarketError;
-use crate::state::{Market, MarketState, Outcome, UserPosition};
+use crate::lifecycle::{require_dispute_window_passed, require_phase};
+use crate::state::{
+    Config, FixedOddsTerms, HousePool, Market, MarketMode, MarketState, MarketType, Outcome,
+    UserPosition,
+};
 
-/// Reveal the committed outcome and claim fixed-odds winnings.
+/// Reveal the committed outcome and claim winnings.
+///
+/// A correct prediction gets its stake back, or in a fixed-odds market the stake times
+/// the market's odds, with everything above the stake paid by the house pool. Losing
+/// fixed-odds stakes go to the house pool.
 ///
 /// The client must pass the same preimage used to construct the commitment:
 /// SHA256(market_id || user_pubkey || outcome_discriminant || salt)
@@ -16,11 +24,19 @@ pub struct RevealAndClaim<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
     #[account(
         mut,
         seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
         bump = market.bump,
-        constraint = market.state == MarketState::Resolved @ PredictionMarketError::MarketNotFinalized
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.mode != MarketMode::Public @ PredictionMarketError::WrongMarketMode
     )]
     pub market: Account<'info, Market>,
 
@@ -47,6 +63,30 @@ pub struct RevealAndClaim<'info> {
     )]
     pub user_token_account: Account<'info, TokenAccount>,
 
+    /// Fixed-odds terms (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [FixedOddsTerms::SEED, market_id.to_le_bytes().as_ref()],
+        bump = fixed_odds.bump
+    )]
+    pub fixed_odds: Option<Account<'info, FixedOddsTerms>>,
+
+    /// House pool (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [HousePool::SEED],
+        bump = house_pool.bump
+    )]
+    pub house_pool: Option<Account<'info, HousePool>>,
+
+    /// House vault (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [HousePool::VAULT_SEED],
+        bump
+    )]
+    pub house_vault: Option<Box<Account<'info, TokenAccount>>>,
+
     pub token_program: Program<'info, Token>,
 }
 
@@ -67,15 +107,9 @@ pub fn handler(
     );
     require!(!position.revealed, PredictionMarketError::AlreadyRevealed);
     
-    // Check reveal deadline has not passed
-    require!(
-        market.reveal_deadline > 0, // Market must be resolved
-        PredictionMarketError::MarketNotFinalized
-    );
-    require!(
-        clock.unix_timestamp <= market.reveal_deadline,
-        PredictionMarketError::RevealDeadlineExpired
-    );
+    // Market must be resolved, past its dispute window and before the reveal deadline
+    require_phase(market, clock.unix_timestamp, &[MarketState::Resolved])?;
+    require_dispute_window_passed(market, clock.unix_timestamp)?;
 
     // Recompute the commitment using SHA256 (same as Solana's hashv)
     let market_id_bytes = market_id.to_le_bytes();
@@ -87,10 +121,10 @@ pub fn handler(
     };
 
     let mut hasher = Sha256::new();
-    hasher.update(&market_id_bytes);
+    hasher.update(market_id_bytes);
     hasher.update(ctx.accounts.user.key().as_ref());
-    hasher.update(&[outcome_byte]);
-    hasher.update(&salt);
+    hasher.update([outcome_byte]);
+    hasher.update(salt);
     let computed = hasher.finalize();
 
     require!(
@@ -102,32 +136,123 @@ pub fn handler(
     position.revealed = true;
     position.revealed_outcome = outcome;
 
-    // Fixed odds payout: 1x (refund stake) on correct prediction, 0 on incorrect
+    // Payout: the stake back on a correct prediction (times the odds in fixed-odds
+    // markets), 0 on incorrect
+    let committed_amount = position.committed_amount;
+    let won = market.winning_outcome == outcome;
     let mut payout: u64 = 0;
-    if market.winning_outcome == outcome {
-        // Winner simply gets back their committed stake
-        payout = position.committed_amount;
+    if won {
+        payout = committed_amount;
     }
 
     // Mark as claimed to prevent double-claim, even if payout is zero
     position.claimed = true;
 
-    // Transfer payout if any
-    if payout > 0 {
-        let market_id_bytes = market_id.to_le_bytes();
-        let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
-        let signer_seeds = &[&seeds[..]];
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+
+    // Settle with the house: pay winnings above the stake, collect losing stakes
+    let mut house_payout: u64 = 0;
+    let mut house_stake: u64 = 0;
+    if market.market_type == MarketType::FixedOdds {
+        let (Some(terms), Some(house_pool), Some(house_vault)) = (
+            ctx.accounts.fixed_odds.as_mut(),
+            ctx.accounts.house_pool.as_mut(),
+            ctx.accounts.house_vault.as_ref(),
+        ) else {
+            return err!(PredictionMarketError::MissingHousePool);
+        };
 
+        // The commit's reservation is no longer needed either way
+        let exposure = terms.house_exposure(committed_amount)?;
+        terms.release_liability(house_pool, exposure);
+
+        if won {
+            house_payout = terms
+                .payout(committed_amount)?
+                .checked_sub(committed_amount)
+                .ok_or(PredictionMarketError::Overflow)?;
+            if house_payout > 0 {
+                let house_seeds = &[HousePool::SEED, &[house_pool.bump]];
+                let house_signer_seeds = &[&house_seeds[..]];
+                let cpi_accounts = Transfer {
+                    from: house_vault.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: house_pool.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    cpi_program.clone(),
+                    cpi_accounts,
+                    house_signer_seeds,
+                );
+                transfer(cpi_ctx, house_payout)?;
+            }
+            house_pool.total_paid = house_pool
+                .total_paid
+                .checked_add(house_payout)
+                .ok_or(PredictionMarketError::Overflow)?;
+        } else {
+            house_stake = committed_amount;
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.market_vault.to_account_info(),
+                to: house_vault.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            };
+            let cpi_ctx =
+                CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds);
+            transfer(cpi_ctx, house_stake)?;
+            house_pool.total_received = house_pool
+                .total_received
+                .checked_add(house_stake)
+                .ok_or(PredictionMarketError::Overflow)?;
+        }
+    }
+
+    // Transfer the stake back if won
+    if payout > 0 {
         let cpi_accounts = Transfer {
             from: ctx.accounts.market_vault.to_account_info(),
             to: ctx.accounts.user_token_account.to_account_info(),
             authority: ctx.accounts.market.to_account_info(),
         };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
         transfer(cpi_ctx, payout)?;
     }
 
+    // Track revealed stake and payout
+    let market = &mut ctx.accounts.market;
+    match outcome {
+        Outcome::Yes => {
+            market.total_revealed_yes = market
+                .total_revealed_yes
+                .checked_add(committed_amount)
+                .ok_or(PredictionMarketError::Overflow)?;
+        }
+        Outcome::No => {
+            market.total_revealed_no = market
+                .total_revealed_no
+                .checked_add(committed_amount)
+                .ok_or(PredictionMarketError::Overflow)?;
+        }
+        Outcome::None => {}
+    }
+    market.total_paid_out = market
+        .total_paid_out
+        .checked_add(payout)
+        .ok_or(PredictionMarketError::Overflow)?;
+    market.total_forfeited = market
+        .total_forfeited
+        .checked_add(house_stake)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    msg!("Commitment revealed");
+    msg!("Market ID: {}", market_id);
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Outcome: {:?}", outcome);
+    msg!("Payout: {}", payout.saturating_add(house_payout));
+
     Ok(())
 }
 
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::error::PredictionMarketError;
+use crate::state::{Market, MarketState};
+
+/// Set a market's stake limits (creator only). Limits apply to later deposits; zero
+/// disables a limit.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SetMarketLimits<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.creator == creator.key() @ PredictionMarketError::UnauthorizedCreator,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn handler(
+    ctx: Context<SetMarketLimits>,
+    market_id: u64,
+    min_bet: u64,
+    max_bet_per_user: u64,
+    max_total_pool: u64,
+) -> Result<()> {
+    for cap in [max_bet_per_user, max_total_pool] {
+        require!(
+            cap == 0 || min_bet <= cap,
+            PredictionMarketError::InvalidStakeLimits
+        );
+    }
+
+    let market = &mut ctx.accounts.market;
+    market.min_bet = min_bet;
+    market.max_bet_per_user = max_bet_per_user;
+    market.max_total_pool = max_total_pool;
+
+    msg!("Market limits updated");
+    msg!("Market ID: {}", market_id);
+    msg!("Min Bet: {}", min_bet);
+    msg!("Max Bet Per User: {}", max_bet_per_user);
+    msg!("Max Total Pool: {}", max_total_pool);
+
+    Ok(())
+}
@@ -0,0 +1,603 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    burn, mint_to, transfer, Burn, Mint, MintTo, Token, TokenAccount, Transfer,
+};
+
+use crate::error::PredictionMarketError;
+use crate::lifecycle::{require_dispute_window_passed, require_phase};
+use crate::state::{
+    enforce_user_limits, Config, Credential, LmsrPool, Market, MarketMode, MarketState,
+    MarketType, Outcome, UserLimits,
+};
+
+/// Deposit collateral and receive one YES and one NO share per token (a complete set).
+///
+/// Only in market maker markets, where every share settles at 1 or 0. In a pari-mutuel
+/// market the winning half of a set would be paid out of the losing pool.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct MintCompleteSet<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_BET) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.mode == MarketMode::Public @ PredictionMarketError::WrongMarketMode,
+        constraint = market.market_type != MarketType::PariMutuel @ PredictionMarketError::WrongMarketType
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// User's YES share account
+    #[account(
+        mut,
+        token::mint = yes_mint,
+        token::authority = user
+    )]
+    pub user_yes_account: Box<Account<'info, TokenAccount>>,
+
+    /// User's NO share account
+    #[account(
+        mut,
+        token::mint = no_mint,
+        token::authority = user
+    )]
+    pub user_no_account: Box<Account<'info, TokenAccount>>,
+
+    /// User's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = user
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: User's deposit limits; may not exist. Seeds are checked here and the
+    /// account is deserialized in the handler when it does.
+    #[account(
+        mut,
+        seeds = [UserLimits::SEED, user.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+
+    /// User's credential (markets with a minimum tier only)
+    #[account(
+        seeds = [Credential::SEED, user.key().as_ref()],
+        bump = credential.bump
+    )]
+    pub credential: Option<Box<Account<'info, Credential>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Burn one YES and one NO share per token to withdraw collateral before settlement.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct RedeemCompleteSet<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.mode != MarketMode::Private @ PredictionMarketError::WrongMarketMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// User's YES share account
+    #[account(
+        mut,
+        token::mint = yes_mint,
+        token::authority = user
+    )]
+    pub user_yes_account: Box<Account<'info, TokenAccount>>,
+
+    /// User's NO share account
+    #[account(
+        mut,
+        token::mint = no_mint,
+        token::authority = user
+    )]
+    pub user_no_account: Box<Account<'info, TokenAccount>>,
+
+    /// User's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = user
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Burn outcome shares for their part of the share collateral once the market settles.
+///
+/// On resolution of a pari-mutuel market the winning shares split the share collateral
+/// (both pools plus complete sets) pro rata; in a market maker market they pay 1:1.
+/// Losing shares pay nothing. On cancellation the collateral, less any creator subsidy,
+/// is split equally across all YES and NO shares. Payouts use the live mint supply and
+/// the collateral not yet redeemed, so the last holder takes the remainder.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct RedeemShares<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(Config::PAUSE_CLAIM) @ PredictionMarketError::Paused
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.mode != MarketMode::Private @ PredictionMarketError::WrongMarketMode
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [Market::YES_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.yes_mint_bump
+    )]
+    pub yes_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [Market::NO_MINT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.no_mint_bump
+    )]
+    pub no_mint: Box<Account<'info, Mint>>,
+
+    /// LMSR pool, required for LMSR markets
+    #[account(
+        seeds = [LmsrPool::SEED, market_id.to_le_bytes().as_ref()],
+        bump = lmsr_pool.bump
+    )]
+    pub lmsr_pool: Option<Account<'info, LmsrPool>>,
+
+    /// User's YES or NO share account
+    #[account(
+        mut,
+        token::authority = user
+    )]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+
+    /// User's collateral token account
+    #[account(
+        mut,
+        token::mint = config.token_mint,
+        token::authority = user
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn mint_complete_set_handler(
+    ctx: Context<MintCompleteSet>,
+    market_id: u64,
+    amount: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(amount > 0, PredictionMarketError::ZeroAmount);
+    let clock = Clock::get()?;
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
+    ctx.accounts
+        .market
+        .check_allowlisted(&ctx.accounts.user.key(), &allowlist_proof)?;
+    ctx.accounts.market.check_credential(
+        ctx.accounts.credential.as_deref().map(|credential| &**credential),
+        &ctx.accounts.config.attester,
+        clock.unix_timestamp,
+    )?;
+    enforce_user_limits(&ctx.accounts.user_limits, amount)?;
+
+    // Transfer collateral to vault
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.user_token_account.to_account_info(),
+        to: ctx.accounts.market_vault.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    transfer(cpi_ctx, amount)?;
+
+    // Mint one share of each outcome per token
+    let market = &ctx.accounts.market;
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.yes_mint.to_account_info(),
+        to: ctx.accounts.user_yes_account.to_account_info(),
+        authority: market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    mint_to(cpi_ctx, amount)?;
+
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.no_mint.to_account_info(),
+        to: ctx.accounts.user_no_account.to_account_info(),
+        authority: market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    mint_to(cpi_ctx, amount)?;
+
+    let market = &mut ctx.accounts.market;
+    market.complete_sets = market
+        .complete_sets
+        .checked_add(amount)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    msg!("Complete set minted");
+    msg!("Market ID: {}", market_id);
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Amount: {}", amount);
+    msg!("Complete Sets: {}", market.complete_sets);
+
+    Ok(())
+}
+
+pub fn redeem_complete_set_handler(
+    ctx: Context<RedeemCompleteSet>,
+    market_id: u64,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, PredictionMarketError::ZeroAmount);
+
+    // Only collateral deposited as complete sets can leave this way; bet stakes stay
+    // in the pools and are withdrawn through exit_position.
+    let market = &mut ctx.accounts.market;
+    market.complete_sets = market
+        .complete_sets
+        .checked_sub(amount)
+        .ok_or(PredictionMarketError::InsufficientCompleteSets)?;
+
+    // Burn one share of each outcome per token
+    let cpi_accounts = Burn {
+        mint: ctx.accounts.yes_mint.to_account_info(),
+        from: ctx.accounts.user_yes_account.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    burn(cpi_ctx, amount)?;
+
+    let cpi_accounts = Burn {
+        mint: ctx.accounts.no_mint.to_account_info(),
+        from: ctx.accounts.user_no_account.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    burn(cpi_ctx, amount)?;
+
+    // Return the collateral
+    let market = &ctx.accounts.market;
+    let market_id_bytes = market_id.to_le_bytes();
+    let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.market_vault.to_account_info(),
+        to: ctx.accounts.user_token_account.to_account_info(),
+        authority: market.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    transfer(cpi_ctx, amount)?;
+
+    msg!("Complete set redeemed");
+    msg!("Market ID: {}", market_id);
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Amount: {}", amount);
+    msg!("Complete Sets: {}", market.complete_sets);
+
+    Ok(())
+}
+
+pub fn redeem_shares_handler(
+    ctx: Context<RedeemShares>,
+    market_id: u64,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, PredictionMarketError::ZeroAmount);
+
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+    require_phase(
+        market,
+        clock.unix_timestamp,
+        &[MarketState::Resolved, MarketState::Settled, MarketState::Cancelled],
+    )?;
+    require_dispute_window_passed(market, clock.unix_timestamp)?;
+
+    let share_mint_key = ctx.accounts.user_share_account.mint;
+    let (outcome, share_mint) = if share_mint_key == ctx.accounts.yes_mint.key() {
+        (Outcome::Yes, ctx.accounts.yes_mint.to_account_info())
+    } else if share_mint_key == ctx.accounts.no_mint.key() {
+        (Outcome::No, ctx.accounts.no_mint.to_account_info())
+    } else {
+        return err!(PredictionMarketError::InvalidShareMint);
+    };
+
+    // Creator subsidy is returned separately and never refunded to share holders
+    let subsidy = match market.market_type {
+        MarketType::PariMutuel | MarketType::Cpmm | MarketType::FixedOdds => 0,
+        MarketType::Lmsr => ctx
+            .accounts
+            .lmsr_pool
+            .as_ref()
+            .ok_or(PredictionMarketError::MissingAmmPool)?
+            .unwithdrawn_subsidy(),
+    };
+
+    // Pari-mutuel shares all come from place_bet: each one is a unit of stake in its
+    // pool, paid like a legacy bet on resolution and refunded at cost on cancellation.
+    // Mint supplies are read before this burn.
+    let payout = if market.state == MarketState::Resolved {
+        if outcome != market.winning_outcome {
+            // Losing shares can still be burned, for nothing
+            0
+        } else if market.market_type == MarketType::PariMutuel {
+            market.pari_mutuel_payout(amount)?
+        } else {
+            amount
+        }
+    } else {
+        let total_supply = ctx
+            .accounts
+            .yes_mint
+            .supply
+            .checked_add(ctx.accounts.no_mint.supply)
+            .ok_or(PredictionMarketError::Overflow)?;
+        cancelled_refund(market, amount, total_supply, subsidy)?
+    };
+
+    let cpi_accounts = Burn {
+        mint: share_mint,
+        from: ctx.accounts.user_share_account.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    burn(cpi_ctx, amount)?;
+
+    if payout > 0 {
+        let market_id_bytes = market_id.to_le_bytes();
+        let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, payout)?;
+    }
+
+    let market = &mut ctx.accounts.market;
+    market.share_payouts = market
+        .share_payouts
+        .checked_add(payout)
+        .ok_or(PredictionMarketError::Overflow)?;
+    market.total_paid_out = market
+        .total_paid_out
+        .checked_add(payout)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    msg!("Shares redeemed");
+    msg!("Market ID: {}", market_id);
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Outcome: {:?}", outcome);
+    msg!("Shares: {}", amount);
+    msg!("Payout: {}", payout);
+
+    Ok(())
+}
+
+/// What `amount` shares redeem for in a cancelled market, given the supply of both share
+/// mints before the burn and the creator's unwithdrawn LMSR subsidy. Pari-mutuel shares
+/// are refunded at cost. Market maker shares each get an equal cut of what traders and
+/// complete set holders deposited, whatever was paid for them, so once the market maker
+/// has traded a complete set can return more or less than it was minted for.
+fn cancelled_refund(market: &Market, amount: u64, total_supply: u64, subsidy: u64) -> Result<u64> {
+    if market.market_type == MarketType::PariMutuel {
+        return Ok(amount);
+    }
+    let refundable = market
+        .share_pot_remaining()?
+        .checked_sub(subsidy)
+        .ok_or(PredictionMarketError::AccountingMismatch)?;
+    pro_rata(amount, refundable, total_supply)
+}
+
+/// `amount / supply` of `pot`, rounded down
+fn pro_rata(amount: u64, pot: u64, supply: u64) -> Result<u64> {
+    let share = (amount as u128)
+        .checked_mul(pot as u128)
+        .ok_or(PredictionMarketError::Overflow)?
+        .checked_div(supply as u128)
+        .ok_or(PredictionMarketError::Overflow)?;
+    Ok(share as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::test_utils::zeroed;
+
+    fn cancelled_market(market_type: MarketType) -> Market {
+        let mut market: Market = zeroed(Market::INIT_SPACE);
+        market.market_type = market_type;
+        market.state = MarketState::Cancelled;
+        market
+    }
+
+    /// Redeem each `(holder, shares)` in turn, updating the market and the mint supply the
+    /// way redeem_shares does, and return what each holder received
+    fn redeem_all(
+        market: &mut Market,
+        mut total_supply: u64,
+        subsidy: u64,
+        redemptions: &[(usize, u64)],
+    ) -> Vec<u64> {
+        let holders = redemptions.iter().map(|(holder, _)| holder + 1).max().unwrap();
+        let mut received = vec![0; holders];
+        for &(holder, shares) in redemptions {
+            let payout = cancelled_refund(market, shares, total_supply, subsidy).unwrap();
+            market.share_payouts += payout;
+            total_supply -= shares;
+            received[holder] += payout;
+        }
+        received
+    }
+
+    #[test]
+    fn cancelled_pari_mutuel_refunds_each_bettor_their_stake() {
+        // Alice bets 70 and Carol 30 on YES, Bob 50 on NO
+        let mut market = cancelled_market(MarketType::PariMutuel);
+        market.yes_pool = 100;
+        market.no_pool = 50;
+        market.yes_bet_shares = 100;
+        market.no_bet_shares = 50;
+
+        let received = redeem_all(&mut market, 150, 0, &[(0, 70), (1, 50), (2, 30)]);
+        assert_eq!(received, vec![70, 50, 30]);
+        assert_eq!(market.share_pot_remaining().unwrap(), 0);
+    }
+
+    #[test]
+    fn cancelled_complete_sets_refund_each_minter() {
+        // The creator subsidized an LMSR pool with 70; Carol minted 40 sets and Dan 60
+        let mut market = cancelled_market(MarketType::Lmsr);
+        market.amm_collateral = 70;
+        market.complete_sets = 100;
+
+        let received = redeem_all(
+            &mut market,
+            200,
+            70,
+            &[(0, 40), (1, 60), (0, 40), (1, 60)],
+        );
+        assert_eq!(received, vec![40, 60]);
+        // The subsidy is left for the creator to withdraw
+        assert_eq!(market.share_pot_remaining().unwrap(), 70);
+    }
+
+    #[test]
+    fn cancelled_market_maker_refunds_never_touch_the_subsidy() {
+        // On top of 100 in complete sets, a trader paid 6 for 10 YES shares from the pool
+        let mut market = cancelled_market(MarketType::Lmsr);
+        market.amm_collateral = 76;
+        market.complete_sets = 100;
+
+        let received = redeem_all(
+            &mut market,
+            210,
+            70,
+            &[(0, 40), (1, 60), (2, 10), (0, 40), (1, 60)],
+        );
+        // Holders split the 106 they deposited; rounding dust stays in the vault
+        assert!(received.iter().sum::<u64>() <= 106);
+        assert!(market.share_pot_remaining().unwrap() >= 70);
+    }
+
+    #[test]
+    fn cancelled_market_maker_shares_split_deposits_evenly_after_a_trade() {
+        // Carol minted 40 sets and Dan 60, then Erin paid 50 for 50 YES shares from the
+        // pool: 150 deposited for 250 shares, or 0.6 a share
+        let mut market = cancelled_market(MarketType::Lmsr);
+        market.amm_collateral = 120;
+        market.complete_sets = 100;
+
+        let received = redeem_all(
+            &mut market,
+            250,
+            70,
+            &[(0, 40), (1, 60), (2, 50), (0, 40), (1, 60)],
+        );
+        // A complete set returns 1.2, and Erin gets back only 30 of the 50 paid
+        assert_eq!(received, vec![48, 72, 30]);
+        assert_eq!(market.share_pot_remaining().unwrap(), 70);
+    }
+}
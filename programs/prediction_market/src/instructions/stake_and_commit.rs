@@ -2,7 +2,11 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
 
 use crate::error::PredictionMarketError;
-use crate::state::{Config, Market, MarketState, UserPosition};
+use crate::lifecycle::require_phase;
+use crate::state::{
+    enforce_user_limits, Config, Credential, FixedOddsTerms, HousePool, Market, MarketMode,
+    MarketState, MarketType, UserLimits, UserPosition, UserStake,
+};
 
 /// Stake tokens and store a commitment hash for a binary market.
 ///
@@ -13,6 +17,10 @@ use crate::state::{Config, Market, MarketState, UserPosition};
 /// - user_pubkey: 32 bytes
 /// - outcome_discriminant: 1 byte (0 = None, 1 = Yes, 2 = No)
 /// - salt: 32 random bytes
+///
+/// In fixed-odds markets the commit is assumed to win and its house exposure is reserved
+/// against the house pool, capped by the market's maximum liability. Invite-only markets
+/// require a Merkle proof that the bettor is on the allowlist.
 #[derive(Accounts)]
 #[instruction(market_id: u64)]
 pub struct StakeAndCommit<'info> {
@@ -22,7 +30,7 @@ pub struct StakeAndCommit<'info> {
     #[account(
         seeds = [Config::SEED],
         bump = config.bump,
-        constraint = !config.paused @ PredictionMarketError::Paused
+        constraint = !config.is_paused(Config::PAUSE_COMMIT) @ PredictionMarketError::Paused
     )]
     pub config: Account<'info, Config>,
 
@@ -30,7 +38,9 @@ pub struct StakeAndCommit<'info> {
         mut,
         seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
         bump = market.bump,
-        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive
+        constraint = market.state == MarketState::Active @ PredictionMarketError::MarketNotActive,
+        constraint = !market.paused @ PredictionMarketError::MarketPaused,
+        constraint = market.mode != MarketMode::Public @ PredictionMarketError::WrongMarketMode
     )]
     pub market: Account<'info, Market>,
 
@@ -50,6 +60,32 @@ pub struct StakeAndCommit<'info> {
     )]
     pub user_position: Account<'info, UserPosition>,
 
+    /// Running deposit total for stake limits
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + UserStake::INIT_SPACE,
+        seeds = [UserStake::SEED, market_id.to_le_bytes().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    /// CHECK: Bettor's deposit limits; may not exist. Seeds are checked here and the
+    /// account is deserialized in the handler when it does.
+    #[account(
+        mut,
+        seeds = [UserLimits::SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_limits: UncheckedAccount<'info>,
+
+    /// Bettor's credential (markets with a minimum tier only)
+    #[account(
+        seeds = [Credential::SEED, bettor.key().as_ref()],
+        bump = credential.bump
+    )]
+    pub credential: Option<Box<Account<'info, Credential>>>,
+
     /// Bettor's token account (neutral ticket asset)
     #[account(
         mut,
@@ -58,6 +94,29 @@ pub struct StakeAndCommit<'info> {
     )]
     pub bettor_token_account: Account<'info, TokenAccount>,
 
+    /// Fixed-odds terms (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [FixedOddsTerms::SEED, market_id.to_le_bytes().as_ref()],
+        bump = fixed_odds.bump
+    )]
+    pub fixed_odds: Option<Account<'info, FixedOddsTerms>>,
+
+    /// House pool (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [HousePool::SEED],
+        bump = house_pool.bump
+    )]
+    pub house_pool: Option<Account<'info, HousePool>>,
+
+    /// House vault (fixed-odds markets only)
+    #[account(
+        seeds = [HousePool::VAULT_SEED],
+        bump
+    )]
+    pub house_vault: Option<Box<Account<'info, TokenAccount>>>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
@@ -67,14 +126,12 @@ pub fn handler(
     market_id: u64,
     amount: u64,
     commitment: [u8; 32],
+    allowlist_proof: Vec<[u8; 32]>,
 ) -> Result<()> {
     // Basic validations
     require!(amount > 0, PredictionMarketError::ZeroAmount);
     let clock = Clock::get()?;
-    require!(
-        clock.unix_timestamp < ctx.accounts.market.resolution_time,
-        PredictionMarketError::MarketExpired
-    );
+    require_phase(&ctx.accounts.market, clock.unix_timestamp, &[MarketState::Active])?;
 
     // Only allow a single commitment per user per market for now
     let position = &mut ctx.accounts.user_position;
@@ -85,6 +142,61 @@ pub fn handler(
         );
     }
 
+    ctx.accounts
+        .market
+        .check_allowlisted(&ctx.accounts.bettor.key(), &allowlist_proof)?;
+    ctx.accounts.market.check_credential(
+        ctx.accounts.credential.as_deref().map(|credential| &**credential),
+        &ctx.accounts.config.attester,
+        clock.unix_timestamp,
+    )?;
+
+    // Stake limits: the user's own, then the market's and the protocol's
+    enforce_user_limits(&ctx.accounts.user_limits, amount)?;
+    let user_stake = &mut ctx.accounts.user_stake;
+    ctx.accounts.market.check_stake_limits(
+        amount,
+        user_stake.total_staked,
+        ctx.accounts.config.max_stake_per_wallet,
+    )?;
+    user_stake.market_id = market_id;
+    user_stake.user = ctx.accounts.bettor.key();
+    user_stake.total_staked = user_stake
+        .total_staked
+        .checked_add(amount)
+        .ok_or(PredictionMarketError::Overflow)?;
+    user_stake.bump = ctx.bumps.user_stake;
+
+    // Reserve the worst case (this commit wins) against the house pool
+    if ctx.accounts.market.market_type == MarketType::FixedOdds {
+        let (Some(terms), Some(house_pool), Some(house_vault)) = (
+            ctx.accounts.fixed_odds.as_mut(),
+            ctx.accounts.house_pool.as_mut(),
+            ctx.accounts.house_vault.as_ref(),
+        ) else {
+            return err!(PredictionMarketError::MissingHousePool);
+        };
+        let exposure = terms.house_exposure(amount)?;
+        let reserved_liability = terms
+            .reserved_liability
+            .checked_add(exposure)
+            .ok_or(PredictionMarketError::Overflow)?;
+        require!(
+            reserved_liability <= terms.max_liability,
+            PredictionMarketError::MaxLiabilityExceeded
+        );
+        let total_reserved = house_pool
+            .total_reserved
+            .checked_add(exposure)
+            .ok_or(PredictionMarketError::Overflow)?;
+        require!(
+            total_reserved <= house_vault.amount,
+            PredictionMarketError::InsufficientHouseLiquidity
+        );
+        terms.reserved_liability = reserved_liability;
+        house_pool.total_reserved = total_reserved;
+    }
+
     // Transfer tokens into the market vault
     let cpi_accounts = Transfer {
         from: ctx.accounts.bettor_token_account.to_account_info(),
@@ -95,8 +207,27 @@ pub fn handler(
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
     transfer(cpi_ctx, amount)?;
 
+    ctx.accounts.market.commit_count = ctx
+        .accounts
+        .market
+        .commit_count
+        .checked_add(1)
+        .ok_or(PredictionMarketError::Overflow)?;
+    ctx.accounts.market.total_committed = ctx
+        .accounts
+        .market
+        .total_committed
+        .checked_add(amount)
+        .ok_or(PredictionMarketError::Overflow)?;
+
     // Initialize or update the user position
     if position.market_id == 0 {
+        ctx.accounts.market.open_positions = ctx
+            .accounts
+            .market
+            .open_positions
+            .checked_add(1)
+            .ok_or(PredictionMarketError::Overflow)?;
         position.market_id = market_id;
         position.user = ctx.accounts.bettor.key();
         // Legacy fields are kept at zero for commit-reveal
@@ -104,6 +235,7 @@ pub fn handler(
         position.no_bet = 0;
         position.claimed = false;
         position.bump = ctx.bumps.user_position;
+        position.version = UserPosition::CURRENT_VERSION;
         position.commitment = commitment;
         position.committed_amount = amount;
         position.revealed = false;
@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
+
+use crate::error::PredictionMarketError;
+use crate::lifecycle::require_phase;
+use crate::state::{Config, HousePool, Market, MarketState, MarketType};
+
+/// Sweep the remaining vault balance (rounding dust and unclaimed winnings) to the
+/// protocol fee recipient once the market's claim window has expired, or to the house
+/// pool in fixed-odds markets. Permissionless.
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct SweepMarket<'info> {
+    #[account(
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [Market::SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.bump,
+        constraint = market.state != MarketState::Active @ PredictionMarketError::MarketNotFinalized,
+        constraint = !market.swept @ PredictionMarketError::MarketSwept
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [Market::VAULT_SEED, market_id.to_le_bytes().as_ref()],
+        bump = market.vault_bump
+    )]
+    pub market_vault: Account<'info, TokenAccount>,
+
+    /// Protocol fee recipient token account (receives the swept balance)
+    #[account(
+        mut,
+        token::mint = market_vault.mint,
+        token::authority = config.fee_recipient
+    )]
+    pub fee_recipient_token_account: Account<'info, TokenAccount>,
+
+    /// House pool (fixed-odds markets only)
+    #[account(
+        mut,
+        seeds = [HousePool::SEED],
+        bump = house_pool.bump
+    )]
+    pub house_pool: Option<Account<'info, HousePool>>,
+
+    /// House vault (fixed-odds markets only, receives the swept balance)
+    #[account(
+        mut,
+        seeds = [HousePool::VAULT_SEED],
+        bump
+    )]
+    pub house_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<SweepMarket>, market_id: u64) -> Result<()> {
+    let market = &ctx.accounts.market;
+    let clock = Clock::get()?;
+
+    // Sweeping is only possible once nobody can claim anymore
+    require_phase(market, clock.unix_timestamp, &[MarketState::Closed])?;
+
+    // What is left of a fixed-odds market is unrevealed stakes the house bet against
+    let amount = ctx.accounts.market_vault.amount;
+    let to_house = market.market_type == MarketType::FixedOdds;
+    let recipient = if to_house {
+        let (Some(house_pool), Some(house_vault)) = (
+            ctx.accounts.house_pool.as_mut(),
+            ctx.accounts.house_vault.as_ref(),
+        ) else {
+            return err!(PredictionMarketError::MissingHousePool);
+        };
+        house_pool.total_received = house_pool
+            .total_received
+            .checked_add(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+        house_vault.to_account_info()
+    } else {
+        ctx.accounts.fee_recipient_token_account.to_account_info()
+    };
+
+    if amount > 0 {
+        let market_id_bytes = market_id.to_le_bytes();
+        let seeds = &[Market::SEED, market_id_bytes.as_ref(), &[market.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.market_vault.to_account_info(),
+            to: recipient.clone(),
+            authority: ctx.accounts.market.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+    }
+
+    let market = &mut ctx.accounts.market;
+    market.swept = true;
+    if to_house {
+        market.total_forfeited = market
+            .total_forfeited
+            .checked_add(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+    } else {
+        market.total_fees = market
+            .total_fees
+            .checked_add(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+    }
+
+    msg!("Market swept");
+    msg!("Market ID: {}", market_id);
+    msg!("Amount: {}", amount);
+    msg!("Recipient: {}", recipient.key());
+
+    Ok(())
+}
@@ -1,37 +1,240 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::MAX_FEE_LIMIT;
+use crate::constants::{
+    MAX_FEE_LIMIT, MAX_PRICE_AGE_LIMIT_SECONDS, MAX_TIMELOCK_DELAY_SECONDS,
+    MIN_TIMELOCK_DELAY_SECONDS,
+};
 use crate::error::PredictionMarketError;
-use crate::state::Config;
+use crate::events::{ConfigChangeCancelled, ConfigChangeExecuted, ConfigChangeQueued};
+use crate::state::{Config, ConfigChange, PendingConfigChange, Role};
 
 #[derive(Accounts)]
-pub struct UpdateConfig<'info> {
+#[instruction(change: ConfigChange)]
+pub struct QueueConfigChange<'info> {
     #[account(
-        constraint = admin.key() == config.admin @ PredictionMarketError::InvalidAdmin
+        mut,
+        constraint = config.can_manage_change(&change, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingConfigChange::INIT_SPACE,
+        seeds = [PendingConfigChange::SEED, (config.config_change_counter + 1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Apply a queued change once its timelock has elapsed. Permissionless.
+#[derive(Accounts)]
+#[instruction(change_id: u64)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [Config::SEED],
+        bump = config.bump
     )]
-    pub admin: Signer<'info>,
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PendingConfigChange::SEED, change_id.to_le_bytes().as_ref()],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
 
+    /// CHECK: Rent recipient, validated against pending_change.proposer
     #[account(
         mut,
+        address = pending_change.proposer
+    )]
+    pub proposer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(change_id: u64)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        constraint = config.can_manage_change(&pending_change.change, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
         seeds = [Config::SEED],
         bump = config.bump
     )]
     pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PendingConfigChange::SEED, change_id.to_le_bytes().as_ref()],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+
+    /// CHECK: Rent recipient, validated against pending_change.proposer
+    #[account(
+        mut,
+        address = pending_change.proposer
+    )]
+    pub proposer: AccountInfo<'info>,
+}
+
+/// Queue a config change; it can be executed once `config.timelock_delay` has passed
+pub fn queue_handler(ctx: Context<QueueConfigChange>, change: ConfigChange) -> Result<()> {
+    validate_change(&change)?;
+
+    let clock = Clock::get()?;
+    let config = &mut ctx.accounts.config;
+    config.config_change_counter = config
+        .config_change_counter
+        .checked_add(1)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    let eta = clock
+        .unix_timestamp
+        .checked_add(config.timelock_delay)
+        .ok_or(PredictionMarketError::Overflow)?;
+
+    let pending = &mut ctx.accounts.pending_change;
+    pending.id = config.config_change_counter;
+    pending.change = change;
+    pending.proposer = ctx.accounts.authority.key();
+    pending.queued_at = clock.unix_timestamp;
+    pending.eta = eta;
+    pending.bump = ctx.bumps.pending_change;
+
+    emit!(ConfigChangeQueued {
+        change_id: pending.id,
+        change,
+        proposer: pending.proposer,
+        eta,
+    });
+
+    msg!("Config change queued");
+    msg!("Change ID: {}", pending.id);
+    msg!("Change: {:?}", change);
+    msg!("ETA: {}", eta);
+
+    Ok(())
 }
 
-pub fn handler(ctx: Context<UpdateConfig>, fee_recipient: Pubkey, max_fee_bps: u16) -> Result<()> {
+pub fn execute_handler(ctx: Context<ExecuteConfigChange>, change_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let pending = &ctx.accounts.pending_change;
     require!(
-        max_fee_bps <= MAX_FEE_LIMIT,
-        PredictionMarketError::InvalidFee
+        clock.unix_timestamp >= pending.eta,
+        PredictionMarketError::TimelockNotElapsed
     );
 
+    // Re-validate in case limits changed while the change was queued
+    let change = pending.change;
+    validate_change(&change)?;
+
+    let config = &mut ctx.accounts.config;
+    let previous_holder = match change {
+        ConfigChange::Role { role, .. } => Some(config.role_holder(role)),
+        _ => None,
+    };
+    config.apply_change(&change);
+
+    emit!(ConfigChangeExecuted { change_id, change });
+
+    msg!("Config change executed");
+    msg!("Change ID: {}", change_id);
+    msg!("Change: {:?}", change);
+    if let Some(previous_holder) = previous_holder {
+        msg!("Previous Holder: {}", previous_holder);
+    }
+
+    Ok(())
+}
+
+pub fn cancel_handler(ctx: Context<CancelConfigChange>, change_id: u64) -> Result<()> {
+    let change = ctx.accounts.pending_change.change;
+
+    emit!(ConfigChangeCancelled { change_id, change });
+
+    msg!("Config change cancelled");
+    msg!("Change ID: {}", change_id);
+    msg!("Change: {:?}", change);
+
+    Ok(())
+}
+
+fn validate_change(change: &ConfigChange) -> Result<()> {
+    match *change {
+        ConfigChange::FeeParams { max_fee_bps, .. } => {
+            require!(
+                max_fee_bps <= MAX_FEE_LIMIT,
+                PredictionMarketError::InvalidFee
+            );
+        }
+        ConfigChange::ExitFee { exit_fee_bps } => {
+            require!(
+                exit_fee_bps <= MAX_FEE_LIMIT,
+                PredictionMarketError::InvalidFee
+            );
+        }
+        ConfigChange::TimelockDelay { delay } => {
+            require!(
+                (MIN_TIMELOCK_DELAY_SECONDS..=MAX_TIMELOCK_DELAY_SECONDS).contains(&delay),
+                PredictionMarketError::InvalidTimelockDelay
+            );
+        }
+        ConfigChange::OraclePolicy { max_price_age } => {
+            require!(
+                max_price_age > 0 && max_price_age <= MAX_PRICE_AGE_LIMIT_SECONDS,
+                PredictionMarketError::InvalidMaxPriceAge
+            );
+        }
+        ConfigChange::Role { .. }
+        | ConfigChange::WalletCap { .. }
+        | ConfigChange::Attester { .. } => {}
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetCreationPolicy<'info> {
+    #[account(
+        constraint = config.has_role(Role::CreatorManager, &authority.key()) @ PredictionMarketError::MissingRole
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Config::SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn set_creation_policy_handler(
+    ctx: Context<SetCreationPolicy>,
+    permissionless_creation: bool,
+    creator_bond_amount: u64,
+) -> Result<()> {
     let config = &mut ctx.accounts.config;
-    config.fee_recipient = fee_recipient;
-    config.max_fee_bps = max_fee_bps;
+    config.permissionless_creation = permissionless_creation;
+    config.creator_bond_amount = creator_bond_amount;
 
-    msg!("Config updated");
-    msg!("Fee Recipient: {}", config.fee_recipient);
-    msg!("Max Fee BPS: {}", config.max_fee_bps);
+    msg!("Creation policy updated");
+    msg!("Permissionless Creation: {}", config.permissionless_creation);
+    msg!("Creator Bond: {}", config.creator_bond_amount);
 
     Ok(())
 }
@@ -1,6 +1,10 @@
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
+pub mod lifecycle;
+pub mod math;
+pub mod merkle;
 pub mod state;
 
 use anchor_lang::prelude::*;
@@ -20,36 +24,133 @@ pub mod prediction_market {
         instructions::initialize::handler(ctx, max_fee_bps)
     }
 
-    /// Update the global configuration (admin only)
-    pub fn update_config(
-        ctx: Context<UpdateConfig>,
-        fee_recipient: Pubkey,
-        max_fee_bps: u16,
+    /// Migrate the config account to the current layout version (admin only)
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate::migrate_config_handler(ctx)
+    }
+
+    /// Migrate a market account to the current layout version
+    pub fn migrate_market(ctx: Context<MigrateMarket>, market_id: u64) -> Result<()> {
+        instructions::migrate::migrate_market_handler(ctx, market_id)
+    }
+
+    /// Migrate the caller's position account to the current layout version
+    pub fn migrate_position(ctx: Context<MigratePosition>, market_id: u64) -> Result<()> {
+        instructions::migrate::migrate_position_handler(ctx, market_id)
+    }
+
+    /// Queue a timelocked configuration change (fee manager for fee params, admin otherwise)
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
+        change: ConfigChange,
     ) -> Result<()> {
-        instructions::update_config::handler(ctx, fee_recipient, max_fee_bps)
+        instructions::update_config::queue_handler(ctx, change)
+    }
+
+    /// Apply a queued configuration change once its timelock has elapsed
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>, change_id: u64) -> Result<()> {
+        instructions::update_config::execute_handler(ctx, change_id)
+    }
+
+    /// Cancel a queued configuration change
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>, change_id: u64) -> Result<()> {
+        instructions::update_config::cancel_handler(ctx, change_id)
+    }
+
+    /// Propose a new admin; takes effect once accepted (admin only)
+    pub fn propose_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::admin_transfer::propose_admin_handler(ctx, new_admin)
+    }
+
+    /// Accept a pending admin transfer (pending admin only)
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::admin_transfer::accept_admin_handler(ctx)
+    }
+
+    /// Cancel a pending admin transfer (admin only)
+    pub fn cancel_admin_transfer(ctx: Context<TransferAdmin>) -> Result<()> {
+        instructions::admin_transfer::cancel_admin_transfer_handler(ctx)
+    }
+
+    /// Permanently renounce admin control (admin only)
+    pub fn renounce_admin(ctx: Context<TransferAdmin>) -> Result<()> {
+        instructions::admin_transfer::renounce_admin_handler(ctx)
+    }
+
+    /// Enable or disable bonded permissionless market creation (creator manager)
+    pub fn set_creation_policy(
+        ctx: Context<SetCreationPolicy>,
+        permissionless_creation: bool,
+        creator_bond_amount: u64,
+    ) -> Result<()> {
+        instructions::update_config::set_creation_policy_handler(
+            ctx,
+            permissionless_creation,
+            creator_bond_amount,
+        )
     }
 
-    /// Pause the contract (admin only)
+    /// Pause every action (pauser)
     pub fn pause(ctx: Context<Pause>) -> Result<()> {
         instructions::pause::pause_handler(ctx)
     }
 
-    /// Unpause the contract (admin only)
+    /// Clear every pause flag (pauser)
     pub fn unpause(ctx: Context<Pause>) -> Result<()> {
         instructions::pause::unpause_handler(ctx)
     }
 
+    /// Pause individual actions with a `Config::PAUSE_*` bitmask (pauser)
+    pub fn set_pause_flags(ctx: Context<Pause>, pause_flags: u8) -> Result<()> {
+        instructions::pause::set_pause_flags_handler(ctx, pause_flags)
+    }
+
+    /// Freeze or unfreeze a single market (pauser)
+    pub fn set_market_paused(
+        ctx: Context<PauseMarket>,
+        market_id: u64,
+        paused: bool,
+    ) -> Result<()> {
+        instructions::pause::set_market_paused_handler(ctx, market_id, paused)
+    }
+
+    /// Add a creator to the market creation allowlist, or update its limits (creator manager)
+    pub fn add_creator(
+        ctx: Context<AddCreator>,
+        creator: Pubkey,
+        max_open_markets: u32,
+        allowed_feeds: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::creator_allowlist::add_creator_handler(
+            ctx,
+            creator,
+            max_open_markets,
+            allowed_feeds,
+        )
+    }
+
+    /// Remove a creator from the market creation allowlist, keeping its entry so open
+    /// markets still free their slots (creator manager)
+    pub fn remove_creator(ctx: Context<RemoveCreator>, creator: Pubkey) -> Result<()> {
+        instructions::creator_allowlist::remove_creator_handler(ctx, creator)
+    }
+
     /// Create a new prediction market
-    /// 
+    ///
+    /// Allowlisted creators pass their allowlist entry; anyone else posts the configured
+    /// creator bond, which is locked until the market settles.
     /// All markets use Pyth oracle for SOL/USD price resolution
     /// - price_threshold: Price threshold in Pyth's native format (accounting for exponent)
     ///   Example: For $160 threshold with exponent -8, use 160 * 10^8 = 16000000000
+    /// - mode: Public markets take place_bet and outcome shares; private markets take
+    ///   stake_and_commit. The mode cannot be changed later.
     pub fn create_market(
         ctx: Context<CreateMarket>,
         question: String,
         resolution_time: i64,
         fee_amount: u64,
         price_threshold: i64,
+        mode: MarketMode,
     ) -> Result<()> {
         instructions::create_market::handler(
             ctx,
@@ -57,20 +158,321 @@ pub mod prediction_market {
             resolution_time,
             fee_amount,
             price_threshold,
+            mode,
         )
     }
 
-    /// Place a bet on a market
+    /// Slash the creator bond of a malformed or misleading market (creator manager)
+    pub fn slash_bond(ctx: Context<SlashBond>, market_id: u64) -> Result<()> {
+        instructions::creator_bond::slash_bond_handler(ctx, market_id)
+    }
+
+    /// Send a settled market's escrowed creation fee to the fee recipient
+    pub fn collect_creation_fee(ctx: Context<CollectCreationFee>, market_id: u64) -> Result<()> {
+        instructions::creator_bond::collect_creation_fee_handler(ctx, market_id)
+    }
+
+    /// Return the creator bond after the market has settled
+    pub fn release_bond(ctx: Context<ReleaseBond>, market_id: u64) -> Result<()> {
+        instructions::creator_bond::release_bond_handler(ctx, market_id)
+    }
+
+    /// Set the signer's daily and weekly deposit caps (0 = no cap). Lowering applies now,
+    /// raising or removing a cap waits out a cooling-off period.
+    pub fn set_deposit_limits(
+        ctx: Context<SetUserLimits>,
+        daily_cap: u64,
+        weekly_cap: u64,
+    ) -> Result<()> {
+        instructions::deposit_limits::set_deposit_limits_handler(ctx, daily_cap, weekly_cap)
+    }
+
+    /// Exclude the signer from depositing anywhere in the protocol for `duration` seconds
+    pub fn self_exclude(ctx: Context<SetUserLimits>, duration: i64) -> Result<()> {
+        instructions::deposit_limits::self_exclude_handler(ctx, duration)
+    }
+
+    /// Record an attester-signed credential for the signer. The previous instruction must
+    /// be an ed25519 signature check of `Credential::message` by the registered attester.
+    pub fn record_credential(
+        ctx: Context<RecordCredential>,
+        tier: u8,
+        issued_at: i64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::credentials::record_credential_handler(ctx, tier, issued_at, expires_at)
+    }
+
+    /// Revoke a wallet's credential (attester)
+    pub fn revoke_credential(ctx: Context<RevokeCredential>, wallet: Pubkey) -> Result<()> {
+        instructions::credentials::revoke_credential_handler(ctx, wallet)
+    }
+
+    /// Require a minimum credential tier to enter a market (creator, 0 = none)
+    pub fn set_min_tier(ctx: Context<SetMinTier>, market_id: u64, min_tier: u8) -> Result<()> {
+        instructions::credentials::set_min_tier_handler(ctx, market_id, min_tier)
+    }
+
+    /// Set a market's minimum bet, per-user cap and total pool cap (creator, 0 = no limit)
+    pub fn set_market_limits(
+        ctx: Context<SetMarketLimits>,
+        market_id: u64,
+        min_bet: u64,
+        max_bet_per_user: u64,
+        max_total_pool: u64,
+    ) -> Result<()> {
+        instructions::set_market_limits::handler(
+            ctx,
+            market_id,
+            min_bet,
+            max_bet_per_user,
+            max_total_pool,
+        )
+    }
+
+    /// Place a bet on a market, reverting if the post-bet payout multiplier
+    /// falls below `min_payout_multiplier_bps` (0 disables the check).
+    /// `allowlist_proof` is only needed for invite-only markets.
     pub fn place_bet(
         ctx: Context<PlaceBet>,
         market_id: u64,
         outcome: Outcome,
         amount: u64,
+        min_payout_multiplier_bps: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::place_bet::handler(
+            ctx,
+            market_id,
+            outcome,
+            amount,
+            min_payout_multiplier_bps,
+            allowlist_proof,
+        )
+    }
+
+    /// Withdraw some or all of a bet before the betting cutoff, paying the market's exit fee
+    pub fn exit_position(
+        ctx: Context<ExitPosition>,
+        market_id: u64,
+        outcome: Outcome,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::exit_position::handler(ctx, market_id, outcome, amount)
+    }
+
+    /// Deposit collateral for one YES and one NO share per token
+    /// `allowlist_proof` is only needed for invite-only markets.
+    pub fn mint_complete_set(
+        ctx: Context<MintCompleteSet>,
+        market_id: u64,
+        amount: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::shares::mint_complete_set_handler(ctx, market_id, amount, allowlist_proof)
+    }
+
+    /// Burn one YES and one NO share per token to withdraw collateral before settlement
+    pub fn redeem_complete_set(
+        ctx: Context<RedeemCompleteSet>,
+        market_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::shares::redeem_complete_set_handler(ctx, market_id, amount)
+    }
+
+    /// Turn a fresh market into an LMSR market funded by a creator subsidy of `b * ln2`
+    pub fn init_lmsr_pool(
+        ctx: Context<InitLmsrPool>,
+        market_id: u64,
+        liquidity: u64,
+    ) -> Result<()> {
+        instructions::lmsr::init_lmsr_pool_handler(ctx, market_id, liquidity)
+    }
+
+    /// Buy outcome shares from an LMSR market, paying at most `max_cost`
+    /// `allowlist_proof` is only needed for invite-only markets.
+    pub fn buy_lmsr_shares(
+        ctx: Context<BuyLmsrShares>,
+        market_id: u64,
+        outcome: Outcome,
+        shares: u64,
+        max_cost: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::lmsr::buy_lmsr_shares_handler(
+            ctx,
+            market_id,
+            outcome,
+            shares,
+            max_cost,
+            allowlist_proof,
+        )
+    }
+
+    /// Sell outcome shares back to an LMSR market for at least `min_proceeds`
+    pub fn sell_lmsr_shares(
+        ctx: Context<SellLmsrShares>,
+        market_id: u64,
+        outcome: Outcome,
+        shares: u64,
+        min_proceeds: u64,
+    ) -> Result<()> {
+        instructions::lmsr::sell_lmsr_shares_handler(ctx, market_id, outcome, shares, min_proceeds)
+    }
+
+    /// Return the LMSR creator subsidy, or its leftover, once the market settles
+    pub fn withdraw_lmsr_subsidy(ctx: Context<WithdrawLmsrSubsidy>, market_id: u64) -> Result<()> {
+        instructions::lmsr::withdraw_lmsr_subsidy_handler(ctx, market_id)
+    }
+
+    /// Turn a fresh market into a constant-product market with the given trading fee
+    pub fn init_cpmm_pool(ctx: Context<InitCpmmPool>, market_id: u64, fee_bps: u16) -> Result<()> {
+        instructions::cpmm::init_cpmm_pool_handler(ctx, market_id, fee_bps)
+    }
+
+    /// Deposit collateral into a constant-product pool for LP tokens
+    /// `allowlist_proof` is only needed for invite-only markets.
+    pub fn add_liquidity(
+        ctx: Context<AddLiquidity>,
+        market_id: u64,
+        amount: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::cpmm::add_liquidity_handler(ctx, market_id, amount, allowlist_proof)
+    }
+
+    /// Burn LP tokens for a proportional cut of the pool's YES and NO reserves
+    pub fn remove_liquidity(
+        ctx: Context<RemoveLiquidity>,
+        market_id: u64,
+        lp_amount: u64,
     ) -> Result<()> {
-        instructions::place_bet::handler(ctx, market_id, outcome, amount)
+        instructions::cpmm::remove_liquidity_handler(ctx, market_id, lp_amount)
     }
 
-    /// Resolve a market (admin only)
+    /// Buy outcome shares from a constant-product pool, receiving at least `min_shares_out`
+    /// `allowlist_proof` is only needed for invite-only markets.
+    pub fn buy_cpmm_shares(
+        ctx: Context<TradeCpmm>,
+        market_id: u64,
+        outcome: Outcome,
+        investment: u64,
+        min_shares_out: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::cpmm::buy_cpmm_shares_handler(
+            ctx,
+            market_id,
+            outcome,
+            investment,
+            min_shares_out,
+            allowlist_proof,
+        )
+    }
+
+    /// Sell outcome shares to a constant-product pool for `return_amount`, spending at
+    /// most `max_shares_in`
+    pub fn sell_cpmm_shares(
+        ctx: Context<TradeCpmm>,
+        market_id: u64,
+        outcome: Outcome,
+        return_amount: u64,
+        max_shares_in: u64,
+    ) -> Result<()> {
+        instructions::cpmm::sell_cpmm_shares_handler(
+            ctx,
+            market_id,
+            outcome,
+            return_amount,
+            max_shares_in,
+        )
+    }
+
+    /// Open a limit order book for a market's YES shares
+    pub fn init_order_book(ctx: Context<InitOrderBook>, market_id: u64) -> Result<()> {
+        instructions::orders::init_order_book_handler(ctx, market_id)
+    }
+
+    /// Place a resting limit order for YES shares, escrowing collateral or shares
+    /// `allowlist_proof` is only needed for invite-only markets.
+    pub fn place_order(
+        ctx: Context<PlaceOrder>,
+        market_id: u64,
+        side: Side,
+        price_bps: u16,
+        quantity: u64,
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::orders::place_order_handler(
+            ctx,
+            market_id,
+            side,
+            price_bps,
+            quantity,
+            allowlist_proof,
+        )
+    }
+
+    /// Cancel a resting order, refunding its escrow to the owner
+    pub fn cancel_order(ctx: Context<CancelOrder>, market_id: u64, order_id: u64) -> Result<()> {
+        instructions::orders::cancel_order_handler(ctx, market_id, order_id)
+    }
+
+    /// Match up to `limit` crossing orders (permissionless crank)
+    pub fn match_orders(ctx: Context<MatchOrders>, market_id: u64, limit: u8) -> Result<()> {
+        instructions::orders::match_orders_handler(ctx, market_id, limit)
+    }
+
+    /// Withdraw free collateral and YES shares from the order book
+    pub fn settle_funds(ctx: Context<SettleFunds>, market_id: u64) -> Result<()> {
+        instructions::orders::settle_funds_handler(ctx, market_id)
+    }
+
+    /// Cancel a trader's resting orders and settle their book balance to their associated
+    /// token accounts once the market is finalized (permissionless)
+    pub fn settle_funds_for(ctx: Context<SettleFundsFor>, market_id: u64) -> Result<()> {
+        instructions::orders::settle_funds_for_handler(ctx, market_id)
+    }
+
+    /// Open the house liquidity pool that backs fixed-odds markets (admin)
+    pub fn init_house_pool(ctx: Context<InitHousePool>) -> Result<()> {
+        instructions::house::init_house_pool_handler(ctx)
+    }
+
+    /// Deposit collateral into the house pool for LP tokens
+    pub fn deposit_house_liquidity(ctx: Context<DepositHouseLiquidity>, amount: u64) -> Result<()> {
+        instructions::house::deposit_house_liquidity_handler(ctx, amount)
+    }
+
+    /// Burn house LP tokens for their share of the unreserved house vault
+    pub fn withdraw_house_liquidity(
+        ctx: Context<WithdrawHouseLiquidity>,
+        lp_amount: u64,
+    ) -> Result<()> {
+        instructions::house::withdraw_house_liquidity_handler(ctx, lp_amount)
+    }
+
+    /// Turn a fresh market into a fixed-odds market with a cap on the house's exposure,
+    /// co-signed by the creator manager
+    pub fn init_fixed_odds(
+        ctx: Context<InitFixedOdds>,
+        market_id: u64,
+        odds_bps: u32,
+        max_liability: u64,
+    ) -> Result<()> {
+        instructions::house::init_fixed_odds_handler(ctx, market_id, odds_bps, max_liability)
+    }
+
+    /// Release a settled fixed-odds market's remaining house reservation (permissionless)
+    pub fn release_fixed_odds_liability(
+        ctx: Context<ReleaseFixedOddsLiability>,
+        market_id: u64,
+    ) -> Result<()> {
+        instructions::house::release_fixed_odds_liability_handler(ctx, market_id)
+    }
+
+    /// Resolve a market (resolver)
     pub fn resolve_market(
         ctx: Context<ResolveMarket>,
         market_id: u64,
@@ -79,27 +481,97 @@ pub mod prediction_market {
         instructions::resolve_market::handler(ctx, market_id, winning_outcome)
     }
 
-    /// Cancel a market (admin only)
+    /// Cancel a market once its resolution time has passed (canceller)
     pub fn cancel_market(ctx: Context<CancelMarket>, market_id: u64) -> Result<()> {
-        instructions::cancel_market::handler(ctx, market_id)
+        instructions::cancel_market::cancel_market_handler(ctx, market_id)
+    }
+
+    /// Withdraw a market before anyone has bet, committed, traded or provided liquidity
+    /// (creator only), refunding the escrowed creation fee
+    pub fn creator_cancel_market(ctx: Context<CreatorCancelMarket>, market_id: u64) -> Result<()> {
+        instructions::cancel_market::creator_cancel_handler(ctx, market_id)
+    }
+
+    /// Cancel an unresolved market at any time, recording a reason code (admin only).
+    /// Bets, shares and commitments are refunded through the usual claim paths.
+    pub fn emergency_cancel_market(
+        ctx: Context<EmergencyCancelMarket>,
+        market_id: u64,
+        reason_code: u16,
+    ) -> Result<()> {
+        instructions::cancel_market::emergency_cancel_handler(ctx, market_id, reason_code)
+    }
+
+    /// Contest a resolved market's outcome once, during the dispute window after resolution
+    /// (canceller), freezing its payouts until the dispute is settled
+    pub fn dispute_market(ctx: Context<DisputeMarket>, market_id: u64) -> Result<()> {
+        instructions::dispute::dispute_market_handler(ctx, market_id)
+    }
+
+    /// Uphold a disputed resolution, or overturn it into a full refund if nothing has
+    /// been settled yet (admin only)
+    pub fn settle_dispute(ctx: Context<SettleDispute>, market_id: u64, uphold: bool) -> Result<()> {
+        instructions::dispute::settle_dispute_handler(ctx, market_id, uphold)
     }
 
-    /// Claim winnings from a resolved or cancelled market
+    /// Claim winnings or refunds for a position (pre-share bets and unrevealed commitments)
     pub fn claim_winnings(ctx: Context<ClaimWinnings>, market_id: u64) -> Result<()> {
         instructions::claim_winnings::handler(ctx, market_id)
     }
 
-    /// Stake tokens and commit to a blind prediction (commit-reveal scheme)
+    /// Burn outcome shares for their part of the collateral from a resolved or cancelled market
+    pub fn redeem_shares(ctx: Context<RedeemShares>, market_id: u64, amount: u64) -> Result<()> {
+        instructions::shares::redeem_shares_handler(ctx, market_id, amount)
+    }
+
+    /// Assert that a market's vault covers its outstanding liabilities
+    pub fn audit_market(ctx: Context<AuditMarket>, market_id: u64) -> Result<()> {
+        instructions::audit_market::handler(ctx, market_id)
+    }
+
+    /// Sweep dust and unclaimed winnings to the fee recipient after the claim window expires
+    pub fn sweep_market(ctx: Context<SweepMarket>, market_id: u64) -> Result<()> {
+        instructions::sweep_market::handler(ctx, market_id)
+    }
+
+    /// Close a claimed or forfeited position and reclaim its rent
+    pub fn close_position(ctx: Context<ClosePosition>, market_id: u64) -> Result<()> {
+        instructions::close_accounts::close_position_handler(ctx, market_id)
+    }
+
+    /// Close a settled market's stake-limit record and reclaim its rent
+    pub fn close_user_stake(ctx: Context<CloseUserStake>, market_id: u64) -> Result<()> {
+        instructions::close_accounts::close_user_stake_handler(ctx, market_id)
+    }
+
+    /// Close a settled market and its vaults once every position is closed (rent to creator)
+    pub fn close_market(ctx: Context<CloseMarket>, market_id: u64) -> Result<()> {
+        instructions::close_accounts::close_market_handler(ctx, market_id)
+    }
+
+    /// Stake tokens and commit to a blind prediction (commit-reveal scheme).
+    /// `allowlist_proof` is only needed for invite-only markets.
     pub fn stake_and_commit(
         ctx: Context<StakeAndCommit>,
         market_id: u64,
         amount: u64,
         commitment: [u8; 32],
+        allowlist_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::stake_and_commit::handler(ctx, market_id, amount, commitment, allowlist_proof)
+    }
+
+    /// Attach, rotate or remove a market's Merkle allowlist before the betting cutoff (creator)
+    pub fn set_allowlist_root(
+        ctx: Context<SetAllowlistRoot>,
+        market_id: u64,
+        root: Option<[u8; 32]>,
     ) -> Result<()> {
-        instructions::stake_and_commit::handler(ctx, market_id, amount, commitment)
+        instructions::market_allowlist::handler(ctx, market_id, root)
     }
 
-    /// Reveal the committed outcome and claim fixed-odds winnings (5x payout)
+    /// Reveal the committed outcome and claim: the stake back on a correct prediction, or
+    /// the market's fixed-odds payout from the house pool
     pub fn reveal_and_claim(
         ctx: Context<RevealAndClaim>,
         market_id: u64,
@@ -109,7 +581,7 @@ pub mod prediction_market {
         instructions::reveal_and_claim::handler(ctx, market_id, outcome, salt)
     }
 
-    /// Forfeit unrevealed stakes to protocol after reveal deadline (admin only)
+    /// Forfeit unrevealed stakes to protocol after reveal deadline (resolver)
     /// Transfers unrevealed committed stakes to protocol fee recipient
     pub fn forfeit_unrevealed(
         ctx: Context<ForfeitUnrevealed>,
@@ -0,0 +1,240 @@
+//! Market lifecycle state machine.
+//!
+//! `Market::state` only stores the states entered by an instruction: Active, Resolved,
+//! Disputed and Cancelled. Betting closing, the reveal window ending and claims expiring
+//! happen with the clock, so those phases are derived from the market's deadlines.
+//! Instructions check the effective phase with `require_phase` and change the stored
+//! state only through `transition`, which rejects illegal moves and sets the deadlines
+//! of the new state.
+//!
+//! Active -> BettingClosed -> Resolved -> Settled -> Closed, where an unresolved market
+//! can instead be Cancelled (then Closed once claims expire), and a Resolved market can be
+//! Disputed once, during a short window after resolution, until the admin upholds the
+//! resolution or overturns it into a cancellation. Nothing is paid out under a resolution
+//! while it can still be disputed.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{CLAIM_WINDOW_SECONDS, DISPUTE_WINDOW_SECONDS, REVEAL_WINDOW_SECONDS};
+use crate::error::PredictionMarketError;
+use crate::events::MarketStateChanged;
+use crate::state::{Market, MarketState, Outcome};
+
+/// Effective phase of `market` at `now`.
+///
+/// Markets finalized before claim expiry existed (claim_expiry 0) never close, so their
+/// remaining funds stay claimable instead of becoming sweepable.
+pub fn phase(market: &Market, now: i64) -> MarketState {
+    match market.state {
+        MarketState::Active if now >= market.resolution_time => MarketState::BettingClosed,
+        MarketState::Resolved | MarketState::Cancelled
+            if market.claim_expiry > 0 && now > market.claim_expiry =>
+        {
+            MarketState::Closed
+        }
+        MarketState::Resolved if now > market.reveal_deadline => MarketState::Settled,
+        state => state,
+    }
+}
+
+/// Position of a phase along the market's timeline; Resolved, Disputed and Cancelled
+/// are alternative branches at the same step
+fn step(state: MarketState) -> u8 {
+    match state {
+        MarketState::Active => 0,
+        MarketState::BettingClosed => 1,
+        MarketState::Resolved | MarketState::Disputed | MarketState::Cancelled => 2,
+        MarketState::Settled => 3,
+        MarketState::Closed => 4,
+    }
+}
+
+/// Require the effective phase to be one of `allowed` and return it.
+///
+/// The error says how the market is off: not yet at the earliest allowed phase (named
+/// after the deadline it is waiting for), already past the latest one, disputed, or on
+/// another branch.
+pub fn require_phase(market: &Market, now: i64, allowed: &[MarketState]) -> Result<MarketState> {
+    let current = phase(market, now);
+    if allowed.contains(&current) {
+        return Ok(current);
+    }
+
+    let earliest = allowed.iter().map(|s| step(*s)).min().unwrap_or(0);
+    let latest = allowed.iter().map(|s| step(*s)).max().unwrap_or(0);
+    let error = if current == MarketState::Disputed {
+        PredictionMarketError::MarketDisputed
+    } else if current == MarketState::Cancelled && !allowed.contains(&MarketState::Closed) {
+        // A cancelled market only moves on to Closed
+        PredictionMarketError::InvalidMarketState
+    } else if step(current) < earliest {
+        match earliest {
+            1 => PredictionMarketError::MarketNotExpired,
+            2 => PredictionMarketError::MarketNotFinalized,
+            3 => PredictionMarketError::RevealDeadlineNotPassed,
+            _ => PredictionMarketError::ClaimWindowNotExpired,
+        }
+    } else if step(current) > latest {
+        match current {
+            MarketState::BettingClosed => PredictionMarketError::MarketExpired,
+            MarketState::Settled => PredictionMarketError::RevealDeadlineExpired,
+            MarketState::Closed => PredictionMarketError::ClaimWindowExpired,
+            _ => PredictionMarketError::MarketAlreadyFinalized,
+        }
+    } else {
+        PredictionMarketError::InvalidMarketState
+    };
+    Err(error.into())
+}
+
+/// Require that the market's resolution can no longer be disputed before paying out
+/// under it: the dispute window has passed or the one dispute has been settled.
+/// Cancelled markets refund deposits and are not held back.
+pub fn require_dispute_window_passed(market: &Market, now: i64) -> Result<()> {
+    require!(
+        market.state != MarketState::Resolved
+            || market.was_disputed
+            || now >= market.dispute_deadline,
+        PredictionMarketError::DisputeWindowOpen
+    );
+    Ok(())
+}
+
+/// Whether any settlement has happened: payouts, reveals or forfeits
+fn has_settlements(market: &Market) -> bool {
+    market.total_paid_out > 0
+        || market.total_forfeited > 0
+        || market.total_revealed_yes > 0
+        || market.total_revealed_no > 0
+}
+
+/// Move `market` to the stored state `to` and set the deadlines that come with it.
+///
+/// Legal moves:
+/// - BettingClosed -> Resolved: opens the dispute window, the reveal window, then the
+///   claim window
+/// - Active or BettingClosed -> Cancelled: opens the claim window for refunds
+/// - Resolved -> Disputed: only once, while the dispute window is open
+/// - Disputed -> Resolved: the resolution is upheld and every deadline moves back by the
+///   time spent disputed
+/// - Disputed -> Cancelled: the resolution is overturned, only if nothing has been
+///   settled under it yet
+pub fn transition(market: &mut Market, to: MarketState, now: i64) -> Result<()> {
+    let from = phase(market, now);
+    match (from, to) {
+        (MarketState::BettingClosed, MarketState::Resolved) => {
+            market.dispute_deadline = now
+                .checked_add(DISPUTE_WINDOW_SECONDS)
+                .ok_or(PredictionMarketError::Overflow)?;
+            market.reveal_deadline = market
+                .resolution_time
+                .checked_add(REVEAL_WINDOW_SECONDS)
+                .ok_or(PredictionMarketError::Overflow)?;
+            market.claim_expiry = market
+                .reveal_deadline
+                .checked_add(CLAIM_WINDOW_SECONDS)
+                .ok_or(PredictionMarketError::Overflow)?;
+        }
+        (
+            MarketState::Active | MarketState::BettingClosed | MarketState::Disputed,
+            MarketState::Cancelled,
+        ) => {
+            if from == MarketState::Disputed {
+                require!(
+                    !has_settlements(market),
+                    PredictionMarketError::DisputeAfterSettlement
+                );
+                market.winning_outcome = Outcome::None;
+                market.disputed_at = 0;
+            }
+            market.claim_expiry = now
+                .checked_add(CLAIM_WINDOW_SECONDS)
+                .ok_or(PredictionMarketError::Overflow)?;
+        }
+        (MarketState::Resolved, MarketState::Disputed) => {
+            require!(
+                !market.was_disputed && now < market.dispute_deadline,
+                PredictionMarketError::DisputeNotAllowed
+            );
+            market.was_disputed = true;
+            market.disputed_at = now;
+        }
+        (MarketState::Disputed, MarketState::Resolved) => {
+            let frozen = now
+                .checked_sub(market.disputed_at)
+                .ok_or(PredictionMarketError::Overflow)?;
+            market.reveal_deadline = market
+                .reveal_deadline
+                .checked_add(frozen)
+                .ok_or(PredictionMarketError::Overflow)?;
+            market.claim_expiry = market
+                .claim_expiry
+                .checked_add(frozen)
+                .ok_or(PredictionMarketError::Overflow)?;
+            market.disputed_at = 0;
+        }
+        _ => return err!(PredictionMarketError::IllegalStateTransition),
+    }
+
+    market.state = to;
+    emit!(MarketStateChanged {
+        market_id: market.id,
+        from,
+        to,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::test_utils::zeroed;
+
+    const RESOLVED_AT: i64 = 1_000;
+
+    /// A market resolved at `RESOLVED_AT`, right after its resolution time
+    fn resolved_market() -> Market {
+        let mut market: Market = zeroed(Market::INIT_SPACE);
+        market.resolution_time = RESOLVED_AT - 1;
+        transition(&mut market, MarketState::Resolved, RESOLVED_AT).unwrap();
+        market
+    }
+
+    #[test]
+    fn payouts_wait_for_the_dispute_window() {
+        let market = resolved_market();
+        assert_eq!(market.dispute_deadline, RESOLVED_AT + DISPUTE_WINDOW_SECONDS);
+        assert!(require_dispute_window_passed(&market, RESOLVED_AT).is_err());
+        assert!(require_dispute_window_passed(&market, market.dispute_deadline - 1).is_err());
+        assert!(require_dispute_window_passed(&market, market.dispute_deadline).is_ok());
+    }
+
+    #[test]
+    fn market_can_only_be_disputed_once() {
+        let mut market = resolved_market();
+        transition(&mut market, MarketState::Disputed, RESOLVED_AT + 10).unwrap();
+        transition(&mut market, MarketState::Resolved, RESOLVED_AT + 20).unwrap();
+
+        // The upheld resolution pays out right away and cannot be contested again
+        assert!(require_dispute_window_passed(&market, RESOLVED_AT + 20).is_ok());
+        assert!(transition(&mut market, MarketState::Disputed, RESOLVED_AT + 30).is_err());
+        assert_eq!(market.state, MarketState::Resolved);
+    }
+
+    #[test]
+    fn dispute_window_closes() {
+        let mut market = resolved_market();
+        let deadline = market.dispute_deadline;
+        assert!(transition(&mut market, MarketState::Disputed, deadline).is_err());
+        assert!(!market.was_disputed);
+    }
+
+    #[test]
+    fn cancelled_markets_refund_without_waiting() {
+        let mut market: Market = zeroed(Market::INIT_SPACE);
+        market.resolution_time = RESOLVED_AT;
+        transition(&mut market, MarketState::Cancelled, RESOLVED_AT).unwrap();
+        assert!(require_dispute_window_passed(&market, RESOLVED_AT).is_ok());
+    }
+}
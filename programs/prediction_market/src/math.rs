@@ -0,0 +1,317 @@
+//! Market maker math: fixed-point LMSR and constant-product (FPMM) quotes.
+//!
+//! Values are unsigned with 12 decimal places. The cost function is evaluated in the
+//! numerically stable form `C(q) = max(q_yes, q_no) + b * ln(1 + e^(-|q_yes - q_no| / b))`,
+//! so only `e^(-x)` for `x >= 0` and `ln(1 + y)` for `y` in `[0, 1]` are needed. Both
+//! are short series that stay well inside the compute budget.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::error::PredictionMarketError;
+
+/// Fixed-point one (12 decimals)
+pub const FIXED_ONE: u128 = 1_000_000_000_000;
+/// ln(2) in fixed point
+pub const FIXED_LN_2: u128 = 693_147_180_560;
+
+/// Series terms are dropped once they reach zero; these caps bound the worst case
+const EXP_MAX_TERMS: u128 = 24;
+const LN_MAX_TERMS: u128 = 48;
+
+/// e^(-x) for fixed-point `x >= 0`, rounded down
+pub fn exp_neg(x: u128) -> u128 {
+    // e^(-x) = 2^(-k) * e^(-r) with x = k * ln2 + r and 0 <= r < ln2
+    let k = x / FIXED_LN_2;
+    if k >= 64 {
+        return 0;
+    }
+    let r = x - k * FIXED_LN_2;
+
+    // Taylor series for e^r, which converges quickly for r < ln2
+    let mut term = FIXED_ONE;
+    let mut exp_r = FIXED_ONE;
+    let mut i = 1;
+    while i <= EXP_MAX_TERMS {
+        term = term * r / FIXED_ONE / i;
+        if term == 0 {
+            break;
+        }
+        exp_r += term;
+        i += 1;
+    }
+
+    (FIXED_ONE * FIXED_ONE / exp_r) >> k
+}
+
+/// ln(1 + y) for fixed-point `y` in `[0, 1]`, rounded down
+pub fn ln_1p(y: u128) -> u128 {
+    // ln(1 + y) = 2 * atanh(z) with z = y / (2 + y) <= 1/3
+    let z = y * FIXED_ONE / (2 * FIXED_ONE + y);
+    let z_squared = z * z / FIXED_ONE;
+
+    let mut power = z;
+    let mut sum = 0;
+    let mut n = 1;
+    while n <= LN_MAX_TERMS && power > 0 {
+        sum += power / n;
+        power = power * z_squared / FIXED_ONE;
+        n += 2;
+    }
+
+    2 * sum
+}
+
+/// e^(-|q_yes - q_no| / b) in fixed point
+fn exp_neg_spread(q_yes: u64, q_no: u64, liquidity: u64) -> Result<u128> {
+    require!(liquidity > 0, PredictionMarketError::InvalidLiquidity);
+    let spread = q_yes.abs_diff(q_no) as u128;
+    let x = spread
+        .checked_mul(FIXED_ONE)
+        .ok_or(PredictionMarketError::Overflow)?
+        / liquidity as u128;
+    Ok(exp_neg(x))
+}
+
+/// LMSR cost function `b * ln(e^(q_yes / b) + e^(q_no / b))` in collateral units, rounded down
+pub fn lmsr_cost(q_yes: u64, q_no: u64, liquidity: u64) -> Result<u64> {
+    let ln_term = ln_1p(exp_neg_spread(q_yes, q_no, liquidity)?);
+    let curvature = (liquidity as u128)
+        .checked_mul(ln_term)
+        .ok_or(PredictionMarketError::Overflow)?
+        / FIXED_ONE;
+    let cost = (q_yes.max(q_no) as u128)
+        .checked_add(curvature)
+        .ok_or(PredictionMarketError::Overflow)?;
+    u64::try_from(cost).map_err(|_| PredictionMarketError::Overflow.into())
+}
+
+/// Creator subsidy that covers the market maker's worst-case loss, `b * ln2` rounded up
+pub fn lmsr_subsidy(liquidity: u64) -> Result<u64> {
+    let subsidy = (liquidity as u128)
+        .checked_mul(FIXED_LN_2)
+        .ok_or(PredictionMarketError::Overflow)?
+        .div_ceil(FIXED_ONE);
+    u64::try_from(subsidy).map_err(|_| PredictionMarketError::Overflow.into())
+}
+
+/// Instantaneous YES price (the market's YES probability) in basis points
+pub fn lmsr_yes_price_bps(q_yes: u64, q_no: u64, liquidity: u64) -> Result<u64> {
+    // With d = |q_yes - q_no| / b the leading side is priced at 1 / (1 + e^-d)
+    let e = exp_neg_spread(q_yes, q_no, liquidity)?;
+    let leading = FIXED_ONE * BPS_DENOMINATOR as u128 / (FIXED_ONE + e);
+    let price = if q_yes >= q_no {
+        leading
+    } else {
+        BPS_DENOMINATOR as u128 - leading
+    };
+    Ok(price as u64)
+}
+
+/// FPMM buy: outcome shares paid out for `net_investment` collateral, which the pool has
+/// split into complete sets. The reserve product is kept at least constant.
+pub fn cpmm_shares_out(own_reserve: u64, other_reserve: u64, net_investment: u64) -> Result<u64> {
+    // An empty reserve has no price to trade against
+    require!(
+        own_reserve > 0 && other_reserve > 0,
+        PredictionMarketError::InsufficientLiquidity
+    );
+    let product = (own_reserve as u128)
+        .checked_mul(other_reserve as u128)
+        .ok_or(PredictionMarketError::Overflow)?;
+    let other_after = (other_reserve as u128)
+        .checked_add(net_investment as u128)
+        .ok_or(PredictionMarketError::Overflow)?;
+    let own_after = product.div_ceil(other_after);
+    let shares_out = (own_reserve as u128)
+        .checked_add(net_investment as u128)
+        .and_then(|own| own.checked_sub(own_after))
+        .ok_or(PredictionMarketError::Overflow)?;
+    u64::try_from(shares_out).map_err(|_| PredictionMarketError::Overflow.into())
+}
+
+/// FPMM sell: outcome shares the pool must receive to release `gross_return` complete
+/// sets. The reserve product is kept at least constant.
+pub fn cpmm_shares_in(own_reserve: u64, other_reserve: u64, gross_return: u64) -> Result<u64> {
+    require!(
+        own_reserve > 0 && gross_return < other_reserve,
+        PredictionMarketError::InsufficientLiquidity
+    );
+    let product = (own_reserve as u128)
+        .checked_mul(other_reserve as u128)
+        .ok_or(PredictionMarketError::Overflow)?;
+    let own_after = product.div_ceil((other_reserve - gross_return) as u128);
+    let shares_in = own_after
+        .checked_add(gross_return as u128)
+        .and_then(|own| own.checked_sub(own_reserve as u128))
+        .ok_or(PredictionMarketError::Overflow)?;
+    u64::try_from(shares_in).map_err(|_| PredictionMarketError::Overflow.into())
+}
+
+/// FPMM YES price in basis points: the NO reserve's share of both reserves
+pub fn cpmm_yes_price_bps(yes_reserve: u64, no_reserve: u64) -> u64 {
+    let total = yes_reserve as u128 + no_reserve as u128;
+    if total == 0 {
+        return BPS_DENOMINATOR / 2;
+    }
+    (no_reserve as u128 * BPS_DENOMINATOR as u128 / total) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One collateral unit with 6 decimals
+    const UNIT: u64 = 1_000_000;
+
+    fn assert_close(actual: u128, expected: u128, tolerance: u128) {
+        assert!(
+            actual.abs_diff(expected) <= tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn exp_neg_matches_reference_values() {
+        assert_eq!(exp_neg(0), FIXED_ONE);
+        assert_close(exp_neg(FIXED_LN_2), FIXED_ONE / 2, 1);
+        // e^-1 = 0.367879441171..., e^-5 = 0.006737946999...
+        assert_close(exp_neg(FIXED_ONE), 367_879_441_171, 2);
+        assert_close(exp_neg(5 * FIXED_ONE), 6_737_946_999, 2);
+        // Rounded down, so never above the true value
+        assert!(exp_neg(FIXED_ONE) <= 367_879_441_172);
+    }
+
+    #[test]
+    fn exp_neg_is_monotonic_and_vanishes() {
+        let mut previous = exp_neg(0);
+        for step in 1..200u128 {
+            let value = exp_neg(step * FIXED_ONE / 4);
+            assert!(value <= previous);
+            previous = value;
+        }
+        assert_eq!(exp_neg(64 * FIXED_LN_2), 0);
+        assert_eq!(exp_neg(u128::MAX / FIXED_ONE), 0);
+    }
+
+    #[test]
+    fn ln_1p_matches_reference_values() {
+        assert_eq!(ln_1p(0), 0);
+        // ln 2 = 0.693147180559..., ln 1.5 = 0.405465108108..., ln 1.1 = 0.095310179804...
+        // Each series term is truncated, so the sum may fall a few units short
+        assert_close(ln_1p(FIXED_ONE), FIXED_LN_2, 16);
+        assert!(ln_1p(FIXED_ONE) <= FIXED_LN_2);
+        assert_close(ln_1p(FIXED_ONE / 2), 405_465_108_108, 16);
+        assert_close(ln_1p(FIXED_ONE / 10), 95_310_179_804, 16);
+    }
+
+    #[test]
+    fn lmsr_cost_of_an_untraded_market_is_the_subsidy() {
+        let liquidity = 1_000 * UNIT;
+        let cost = lmsr_cost(0, 0, liquidity).unwrap();
+        let subsidy = lmsr_subsidy(liquidity).unwrap();
+        assert_eq!(subsidy, 693_147_181);
+        assert!(cost <= subsidy && subsidy - cost <= 1);
+        assert_eq!(lmsr_yes_price_bps(0, 0, liquidity).unwrap(), 5_000);
+    }
+
+    #[test]
+    fn lmsr_cost_matches_reference_values() {
+        let liquidity = 100 * UNIT;
+        // 100 * ln(e^1 + e^0) = 131.326168751...
+        assert_close(lmsr_cost(100 * UNIT, 0, liquidity).unwrap() as u128, 131_326_168, 1);
+        // Symmetric in the outcomes, and shifting both quantities shifts the cost
+        assert_eq!(
+            lmsr_cost(0, 100 * UNIT, liquidity).unwrap(),
+            lmsr_cost(100 * UNIT, 0, liquidity).unwrap()
+        );
+        assert_eq!(
+            lmsr_cost(50 * UNIT, 50 * UNIT, liquidity).unwrap(),
+            50 * UNIT + lmsr_cost(0, 0, liquidity).unwrap()
+        );
+        // e / (1 + e) = 73.1058...%
+        assert_eq!(lmsr_yes_price_bps(100 * UNIT, 0, liquidity).unwrap(), 7_310);
+        assert_eq!(lmsr_yes_price_bps(0, 100 * UNIT, liquidity).unwrap(), 2_690);
+    }
+
+    #[test]
+    fn lmsr_cost_handles_extreme_liquidity() {
+        assert!(lmsr_cost(1, 0, 0).is_err());
+
+        // A tiny b prices the leading side at a full unit per share
+        assert_eq!(lmsr_cost(1_000 * UNIT, 0, 1).unwrap(), 1_000 * UNIT);
+        assert_eq!(lmsr_yes_price_bps(1_000 * UNIT, 0, 1).unwrap(), 10_000);
+
+        // A huge b still fits. Spreads below the fixed-point resolution round to zero,
+        // which charges the buyer a full unit per share rather than less than the true cost.
+        let cost = lmsr_cost(0, 0, u64::MAX).unwrap();
+        assert!(cost <= lmsr_subsidy(u64::MAX).unwrap());
+        assert_eq!(lmsr_cost(UNIT, 0, u64::MAX).unwrap() - cost, UNIT);
+        assert_eq!(lmsr_yes_price_bps(u64::MAX, 0, u64::MAX).unwrap(), 7_310);
+
+        // Quantities near u64::MAX overflow instead of wrapping
+        assert!(lmsr_cost(u64::MAX, u64::MAX, UNIT).is_err());
+        assert!(lmsr_subsidy(u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn cpmm_buy_rounds_in_the_pools_favour() {
+        // 100 * 100 = 67 * 150 would need 66.67 shares left, so the pool keeps 67
+        assert_eq!(cpmm_shares_out(100, 100, 50).unwrap(), 83);
+        assert_eq!(cpmm_shares_out(100, 100, 0).unwrap(), 0);
+
+        let trades = [(100, 100, 1), (7, 13, 5), (1_000, 3, 999), (5, 5_000, 1)];
+        for (own, other, investment) in trades {
+            let shares_out = cpmm_shares_out(own, other, investment).unwrap();
+            let product_after =
+                (own + investment - shares_out) as u128 * (other + investment) as u128;
+            assert!(product_after >= own as u128 * other as u128);
+        }
+    }
+
+    #[test]
+    fn cpmm_sell_rounds_in_the_pools_favour() {
+        // 100 * 100 / 70 = 142.86, so the seller pays for 143
+        assert_eq!(cpmm_shares_in(100, 100, 30).unwrap(), 73);
+        assert_eq!(cpmm_shares_in(100, 100, 50).unwrap(), 150);
+
+        let trades = [(100, 100, 1), (7, 13, 5), (1_000, 3, 2), (5, 5_000, 4_999)];
+        for (own, other, gross_return) in trades {
+            let shares_in = cpmm_shares_in(own, other, gross_return).unwrap();
+            let product_after =
+                (own + shares_in - gross_return) as u128 * (other - gross_return) as u128;
+            assert!(product_after >= own as u128 * other as u128);
+        }
+
+        // Selling straight back what a buy paid out never returns the full investment
+        let shares_out = cpmm_shares_out(100 * UNIT, 100 * UNIT, 10 * UNIT).unwrap();
+        let shares_in = cpmm_shares_in(
+            100 * UNIT + 10 * UNIT - shares_out,
+            100 * UNIT + 10 * UNIT,
+            10 * UNIT,
+        )
+        .unwrap();
+        assert!(shares_in >= shares_out);
+    }
+
+    #[test]
+    fn cpmm_rejects_empty_reserves() {
+        assert!(cpmm_shares_out(0, 100, 10).is_err());
+        assert!(cpmm_shares_out(100, 0, 10).is_err());
+        assert!(cpmm_shares_out(0, 0, 0).is_err());
+        assert!(cpmm_shares_in(0, 100, 10).is_err());
+        assert!(cpmm_shares_in(100, 0, 0).is_err());
+        // The pool can never pay out its whole other reserve
+        assert!(cpmm_shares_in(100, 100, 100).is_err());
+        assert_eq!(cpmm_yes_price_bps(0, 0), 5_000);
+    }
+
+    #[test]
+    fn cpmm_quotes_overflow_instead_of_wrapping() {
+        assert!(cpmm_shares_out(u64::MAX, u64::MAX, u64::MAX).is_err());
+        assert!(cpmm_shares_in(u64::MAX, 2, 1).is_err());
+    }
+}
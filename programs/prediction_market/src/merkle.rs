@@ -0,0 +1,38 @@
+//! Merkle allowlists for invite-only markets.
+//!
+//! Leaves are `SHA256(0x00 || wallet)` and inner nodes `SHA256(0x01 || min(a, b) || max(a, b))`.
+//! The domain prefixes keep a leaf from being passed off as an inner node, and sorting each
+//! pair means a proof is just the list of sibling hashes, without left/right flags.
+
+use anchor_lang::prelude::*;
+use sha2::{Digest, Sha256};
+
+/// Longest accepted proof (allowlists of up to 2^20 wallets)
+pub const MAX_PROOF_LEN: usize = 20;
+
+/// Leaf hash for `wallet`
+pub fn allowlist_leaf(wallet: &Pubkey) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(wallet.as_ref());
+    hasher.finalize().into()
+}
+
+/// Parent of two sibling nodes
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+/// Whether `proof` links `leaf` to `root`
+pub fn verify_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    if proof.len() > MAX_PROOF_LEN {
+        return false;
+    }
+    let computed = proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+/// Maximum number of price feeds a single creator can be restricted to
+pub const MAX_CREATOR_FEEDS: usize = 4;
+
+/// Allowlist entry authorizing a wallet to create markets
+#[account]
+#[derive(InitSpace)]
+pub struct AllowedCreator {
+    /// Creator wallet
+    pub creator: Pubkey,
+    /// Maximum number of markets the creator may have open at once (0 = unlimited)
+    pub max_open_markets: u32,
+    /// Number of markets created by this creator that are still active
+    pub open_markets: u32,
+    /// Pyth feed IDs this creator may create markets on (empty = any feed)
+    #[max_len(MAX_CREATOR_FEEDS)]
+    pub allowed_feeds: Vec<[u8; 32]>,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Set by remove_creator. The entry is kept so markets the creator still has open
+    /// can free their slots; add_creator reactivates it.
+    pub deactivated: bool,
+}
+
+impl AllowedCreator {
+    pub const SEED: &'static [u8] = b"allowed_creator";
+
+    /// Whether this creator may create a market on the given feed
+    pub fn is_feed_allowed(&self, feed_id: &[u8; 32]) -> bool {
+        self.allowed_feeds.is_empty() || self.allowed_feeds.contains(feed_id)
+    }
+
+    /// Whether this creator is below its open market limit
+    pub fn has_capacity(&self) -> bool {
+        self.max_open_markets == 0 || self.open_markets < self.max_open_markets
+    }
+}
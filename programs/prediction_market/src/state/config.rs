@@ -1,10 +1,28 @@
 use anchor_lang::prelude::*;
 
+use crate::state::ConfigChange;
+
+/// Privileged roles that can be delegated away from the admin
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum Role {
+    /// Can pause and unpause the protocol
+    Pauser,
+    /// Can resolve markets and forfeit unrevealed stakes
+    Resolver,
+    /// Can cancel markets
+    Canceller,
+    /// Can change fee parameters
+    FeeManager,
+    /// Can manage the creator allowlist, creation policy and creator bonds, and approve
+    /// fixed-odds terms
+    CreatorManager,
+}
+
 /// Global configuration account for the prediction market
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
-    /// Admin authority
+    /// Admin authority (Pubkey::default() once renounced)
     pub admin: Pubkey,
     /// Fee recipient
     pub fee_recipient: Pubkey,
@@ -16,12 +34,130 @@ pub struct Config {
     pub max_fee_bps: u16,
     /// Total markets created
     pub market_counter: u64,
-    /// Paused flag
-    pub paused: bool,
+    /// Pause bitmask (see `Config::PAUSE_*`)
+    pub pause_flags: u8,
     /// Bump seed for PDA
     pub bump: u8,
+    /// Whether non-allowlisted wallets may create markets by posting a bond
+    pub permissionless_creation: bool,
+    /// Bond required from non-allowlisted creators, in the betting mint
+    pub creator_bond_amount: u64,
+    /// Proposed new admin, pending acceptance
+    pub pending_admin: Option<Pubkey>,
+    /// Role holders (Pubkey::default() = unassigned). The admin implicitly holds every role.
+    pub pauser: Pubkey,
+    pub resolver: Pubkey,
+    pub canceller: Pubkey,
+    pub fee_manager: Pubkey,
+    pub creator_manager: Pubkey,
+    /// Delay in seconds between queueing and executing a config change
+    pub timelock_delay: i64,
+    /// Total config changes queued
+    pub config_change_counter: u64,
+    /// Maximum accepted age of a Pyth price update when resolving, in seconds
+    pub max_price_age: u64,
+    /// Account layout version
+    pub version: u8,
+    /// Fee on early exits from pari-mutuel positions, in basis points. Paid to the winners
+    /// on resolution, or kept by the protocol (the fee recipient) on cancellation.
+    pub exit_fee_bps: u16,
+    /// Most a single wallet may deposit into any one market (0 = no cap)
+    pub max_stake_per_wallet: u64,
+    /// Key whose ed25519 signatures issue user credentials (Pubkey::default() = none)
+    pub attester: Pubkey,
+    /// Reserved for future fields; new fields are carved out of this padding
+    /// so existing accounts keep their size. Bump the version when it runs out.
+    pub reserved: [u8; 22],
 }
 
 impl Config {
     pub const SEED: &'static [u8] = b"config";
+    /// Current account layout version (0 = created before versioning)
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Pause flag: market creation
+    pub const PAUSE_CREATE: u8 = 1 << 0;
+    /// Pause flag: place_bet
+    pub const PAUSE_BET: u8 = 1 << 1;
+    /// Pause flag: stake_and_commit
+    pub const PAUSE_COMMIT: u8 = 1 << 2;
+    /// Pause flag: market resolution
+    pub const PAUSE_RESOLVE: u8 = 1 << 3;
+    /// Pause flag: claims, reveals and early exits
+    pub const PAUSE_CLAIM: u8 = 1 << 4;
+    /// Pause flag: forfeiture of unrevealed stakes
+    pub const PAUSE_FORFEIT: u8 = 1 << 5;
+    /// Every pause flag
+    pub const PAUSE_ALL: u8 = Self::PAUSE_CREATE
+        | Self::PAUSE_BET
+        | Self::PAUSE_COMMIT
+        | Self::PAUSE_RESOLVE
+        | Self::PAUSE_CLAIM
+        | Self::PAUSE_FORFEIT;
+    /// Withdraw-only mode: no new markets or stakes, settlement continues
+    pub const PAUSE_WITHDRAW_ONLY: u8 = Self::PAUSE_CREATE | Self::PAUSE_BET | Self::PAUSE_COMMIT;
+
+    /// Whether the given action is paused protocol-wide
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.pause_flags & flag != 0
+    }
+
+    /// Key currently assigned to a role
+    pub fn role_holder(&self, role: Role) -> Pubkey {
+        match role {
+            Role::Pauser => self.pauser,
+            Role::Resolver => self.resolver,
+            Role::Canceller => self.canceller,
+            Role::FeeManager => self.fee_manager,
+            Role::CreatorManager => self.creator_manager,
+        }
+    }
+
+    /// Assign a role to a key (Pubkey::default() to unassign)
+    pub fn set_role_holder(&mut self, role: Role, holder: Pubkey) {
+        match role {
+            Role::Pauser => self.pauser = holder,
+            Role::Resolver => self.resolver = holder,
+            Role::Canceller => self.canceller = holder,
+            Role::FeeManager => self.fee_manager = holder,
+            Role::CreatorManager => self.creator_manager = holder,
+        }
+    }
+
+    /// Whether `key` may queue or cancel the given config change
+    pub fn can_manage_change(&self, change: &ConfigChange, key: &Pubkey) -> bool {
+        match change.required_role() {
+            Some(role) => self.has_role(role, key),
+            None => *key != Pubkey::default() && *key == self.admin,
+        }
+    }
+
+    /// Apply a config change that has cleared the timelock
+    pub fn apply_change(&mut self, change: &ConfigChange) {
+        match *change {
+            ConfigChange::FeeParams {
+                fee_recipient,
+                max_fee_bps,
+            } => {
+                self.fee_recipient = fee_recipient;
+                self.max_fee_bps = max_fee_bps;
+            }
+            ConfigChange::Role { role, holder } => self.set_role_holder(role, holder),
+            ConfigChange::OraclePolicy { max_price_age } => self.max_price_age = max_price_age,
+            ConfigChange::ExitFee { exit_fee_bps } => self.exit_fee_bps = exit_fee_bps,
+            ConfigChange::TimelockDelay { delay } => self.timelock_delay = delay,
+            ConfigChange::WalletCap {
+                max_stake_per_wallet,
+            } => self.max_stake_per_wallet = max_stake_per_wallet,
+            ConfigChange::Attester { attester } => self.attester = attester,
+        }
+    }
+
+    /// Whether `key` may act with `role`: the admin or the assigned role holder
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        if *key == Pubkey::default() {
+            return false;
+        }
+        *key == self.admin || *key == self.role_holder(role)
+    }
 }
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Role;
+
+/// A configuration change that must wait out the timelock before it is applied
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum ConfigChange {
+    /// Update the fee recipient and max fee
+    FeeParams { fee_recipient: Pubkey, max_fee_bps: u16 },
+    /// Assign a role to a key (Pubkey::default() to unassign)
+    Role { role: Role, holder: Pubkey },
+    /// Update the oracle policy
+    OraclePolicy { max_price_age: u64 },
+    /// Update the fee charged on early exits from pari-mutuel positions
+    ExitFee { exit_fee_bps: u16 },
+    /// Update the timelock delay itself
+    TimelockDelay { delay: i64 },
+    /// Update the per-wallet, per-market deposit cap (0 = no cap)
+    WalletCap { max_stake_per_wallet: u64 },
+    /// Register the credential attester (Pubkey::default() to remove)
+    Attester { attester: Pubkey },
+}
+
+impl ConfigChange {
+    /// Role allowed to queue or cancel this change. `None` means admin only.
+    pub fn required_role(&self) -> Option<Role> {
+        match self {
+            ConfigChange::FeeParams { .. } | ConfigChange::ExitFee { .. } => {
+                Some(Role::FeeManager)
+            }
+            ConfigChange::Role { .. }
+            | ConfigChange::OraclePolicy { .. }
+            | ConfigChange::TimelockDelay { .. }
+            | ConfigChange::WalletCap { .. }
+            | ConfigChange::Attester { .. } => None,
+        }
+    }
+}
+
+/// Queued configuration change awaiting execution
+#[account]
+#[derive(InitSpace)]
+pub struct PendingConfigChange {
+    /// Sequential change ID
+    pub id: u64,
+    /// The queued change
+    pub change: ConfigChange,
+    /// Key that queued the change (receives rent back on execute/cancel)
+    pub proposer: Pubkey,
+    /// Queued at timestamp
+    pub queued_at: i64,
+    /// Earliest execution timestamp
+    pub eta: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl PendingConfigChange {
+    pub const SEED: &'static [u8] = b"config_change";
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+/// Constant-product (FPMM) market maker for a market with `MarketType::Cpmm`.
+///
+/// The pool's YES and NO reserves are real share balances held in token accounts owned
+/// by the market. Trading fees are turned into complete sets and added to the reserves,
+/// so they accrue to liquidity providers through their LP tokens.
+#[account]
+#[derive(InitSpace)]
+pub struct CpmmPool {
+    /// Market ID
+    pub market_id: u64,
+    /// Trading fee in basis points
+    pub fee_bps: u16,
+    /// Total trading fees collected
+    pub total_fees: u64,
+    /// LP mint bump seed
+    pub lp_mint_bump: u8,
+    /// YES reserve token account bump seed
+    pub yes_reserve_bump: u8,
+    /// NO reserve token account bump seed
+    pub no_reserve_bump: u8,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl CpmmPool {
+    pub const SEED: &'static [u8] = b"cpmm_pool";
+    pub const LP_MINT_SEED: &'static [u8] = b"lp_mint";
+    pub const YES_RESERVE_SEED: &'static [u8] = b"cpmm_yes";
+    pub const NO_RESERVE_SEED: &'static [u8] = b"cpmm_no";
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+/// Verified-user credential issued by the protocol's attester.
+///
+/// Recorded from an ed25519-signed attestation. A credential only counts while it is
+/// unexpired, issued by the current attester and not revoked after it was issued.
+#[account]
+#[derive(InitSpace)]
+pub struct Credential {
+    /// Credentialed wallet
+    pub wallet: Pubkey,
+    /// Attester that signed the credential
+    pub attester: Pubkey,
+    /// Verification tier (higher is stronger)
+    pub tier: u8,
+    /// When the attester issued the credential
+    pub issued_at: i64,
+    /// Credential is invalid from this timestamp
+    pub expires_at: i64,
+    /// When the attester last revoked the wallet's credential (0 = never)
+    pub revoked_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl Credential {
+    pub const SEED: &'static [u8] = b"credential";
+    /// Prefix of the message the attester signs, so the signature means nothing elsewhere
+    pub const MESSAGE_DOMAIN: &'static [u8] = b"prediction_market:credential:v1";
+
+    /// Signed message: domain || wallet || tier || issued_at (LE) || expires_at (LE)
+    pub fn message(wallet: &Pubkey, tier: u8, issued_at: i64, expires_at: i64) -> Vec<u8> {
+        let mut message = Vec::with_capacity(Self::MESSAGE_DOMAIN.len() + 32 + 1 + 8 + 8);
+        message.extend_from_slice(Self::MESSAGE_DOMAIN);
+        message.extend_from_slice(wallet.as_ref());
+        message.push(tier);
+        message.extend_from_slice(&issued_at.to_le_bytes());
+        message.extend_from_slice(&expires_at.to_le_bytes());
+        message
+    }
+
+    /// Whether an attestation issued at `issued_at` may replace this record. Only newer,
+    /// unexpired credentials issued after any revocation are accepted, so an old
+    /// signature cannot be replayed to undo a revocation or a downgrade. Future-dated
+    /// credentials are refused, or one recorded now would outlive a later revocation.
+    pub fn accepts(&self, attester: &Pubkey, issued_at: i64, expires_at: i64, now: i64) -> bool {
+        let is_new = self.wallet == Pubkey::default();
+        issued_at <= now
+            && now < expires_at
+            && issued_at > self.revoked_at
+            && (is_new || self.attester != *attester || issued_at > self.issued_at)
+    }
+
+    /// Tier this credential grants right now (0 if it does not count)
+    pub fn effective_tier(&self, attester: &Pubkey, now: i64) -> u8 {
+        let valid = self.attester == *attester
+            && *attester != Pubkey::default()
+            && now < self.expires_at
+            && self.revoked_at < self.issued_at;
+        if valid {
+            self.tier
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::error::PredictionMarketError;
+use crate::state::HousePool;
+
+/// Fixed-odds terms for a market with `MarketType::FixedOdds`.
+///
+/// Committed directions are hidden until after resolution, so every commit is assumed to
+/// win: the house reserves `stake * (odds - 1)` for it until the commit is revealed or
+/// the market settles.
+#[account]
+#[derive(InitSpace)]
+pub struct FixedOddsTerms {
+    /// Market ID
+    pub market_id: u64,
+    /// Payout multiplier on the stake for a correct prediction, in basis points
+    pub odds_bps: u32,
+    /// Cap on the house's worst-case exposure to this market
+    pub max_liability: u64,
+    /// House exposure currently reserved for unrevealed commits
+    pub reserved_liability: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl FixedOddsTerms {
+    pub const SEED: &'static [u8] = b"fixed_odds";
+
+    /// Total payout for a winning stake, rounded down
+    pub fn payout(&self, stake: u64) -> Result<u64> {
+        let payout = (stake as u128)
+            .checked_mul(self.odds_bps as u128)
+            .ok_or(PredictionMarketError::Overflow)?
+            / BPS_DENOMINATOR as u128;
+        u64::try_from(payout).map_err(|_| PredictionMarketError::Overflow.into())
+    }
+
+    /// What the house owes on top of the stake if it wins, rounded up
+    pub fn house_exposure(&self, stake: u64) -> Result<u64> {
+        let exposure = (stake as u128)
+            .checked_mul((self.odds_bps as u64 - BPS_DENOMINATOR) as u128)
+            .ok_or(PredictionMarketError::Overflow)?
+            .div_ceil(BPS_DENOMINATOR as u128);
+        u64::try_from(exposure).map_err(|_| PredictionMarketError::Overflow.into())
+    }
+
+    /// Hand up to `amount` of this market's reservation back to the house pool, returning
+    /// how much was released
+    pub fn release_liability(&mut self, house_pool: &mut HousePool, amount: u64) -> u64 {
+        let released = amount.min(self.reserved_liability);
+        self.reserved_liability -= released;
+        house_pool.total_reserved = house_pool.total_reserved.saturating_sub(released);
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn house_pool(total_reserved: u64) -> HousePool {
+        HousePool {
+            total_reserved,
+            total_paid: 0,
+            total_received: 0,
+            lp_mint_bump: 0,
+            vault_bump: 0,
+            bump: 0,
+        }
+    }
+
+    /// 3x odds with `stakes` committed, next to `other_reserved` held by other markets
+    fn committed(stakes: &[u64], other_reserved: u64) -> (FixedOddsTerms, HousePool) {
+        let mut terms = FixedOddsTerms {
+            market_id: 1,
+            odds_bps: 30_000,
+            max_liability: u64::MAX,
+            reserved_liability: 0,
+            bump: 0,
+        };
+        for stake in stakes {
+            terms.reserved_liability += terms.house_exposure(*stake).unwrap();
+        }
+        let pool = house_pool(terms.reserved_liability + other_reserved);
+        (terms, pool)
+    }
+
+    #[test]
+    fn forfeited_commit_releases_its_exposure() {
+        let (mut terms, mut pool) = committed(&[100, 40], 500);
+        let exposure = terms.house_exposure(40).unwrap();
+        assert_eq!(terms.release_liability(&mut pool, exposure), 80);
+        assert_eq!(terms.reserved_liability, 200);
+        assert_eq!(pool.total_reserved, 700);
+    }
+
+    #[test]
+    fn closing_a_market_with_a_forfeited_commit_frees_the_house() {
+        let (mut terms, mut pool) = committed(&[100, 40, 25], 500);
+
+        // One commit is revealed, one forfeited, and the last is never touched
+        for stake in [100, 40] {
+            let exposure = terms.house_exposure(stake).unwrap();
+            terms.release_liability(&mut pool, exposure);
+        }
+        let remaining = terms.reserved_liability;
+        assert_eq!(remaining, 50);
+
+        // Closing the market releases whatever is left, and only that
+        assert_eq!(terms.release_liability(&mut pool, remaining), 50);
+        assert_eq!(terms.reserved_liability, 0);
+        assert_eq!(pool.total_reserved, 500);
+        assert_eq!(terms.release_liability(&mut pool, u64::MAX), 0);
+        assert_eq!(pool.total_reserved, 500);
+    }
+}
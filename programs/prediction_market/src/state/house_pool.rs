@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+/// House liquidity pool backing fixed-odds markets.
+///
+/// LPs deposit collateral for LP tokens that track a share of the house vault. Fixed-odds
+/// commits reserve their worst-case winnings against the vault, and only the unreserved
+/// balance can be withdrawn.
+#[account]
+#[derive(InitSpace)]
+pub struct HousePool {
+    /// Worst-case winnings reserved by fixed-odds markets across the protocol
+    pub total_reserved: u64,
+    /// Total paid to fixed-odds winners
+    pub total_paid: u64,
+    /// Total losing fixed-odds stakes received
+    pub total_received: u64,
+    /// LP token mint bump seed
+    pub lp_mint_bump: u8,
+    /// House vault bump seed
+    pub vault_bump: u8,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl HousePool {
+    pub const SEED: &'static [u8] = b"house_pool";
+    pub const VAULT_SEED: &'static [u8] = b"house_vault";
+    pub const LP_MINT_SEED: &'static [u8] = b"house_lp_mint";
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::math::{lmsr_cost, lmsr_yes_price_bps};
+
+/// LMSR market maker for a market with `MarketType::Lmsr`.
+///
+/// The pool sells YES and NO shares at the difference of the cost function before and
+/// after each trade. The creator funds the worst-case loss `b * ln2` up front.
+#[account]
+#[derive(InitSpace)]
+pub struct LmsrPool {
+    /// Market ID
+    pub market_id: u64,
+    /// Liquidity parameter `b`, in collateral units
+    pub liquidity: u64,
+    /// YES shares sold by the pool and not bought back
+    pub q_yes: u64,
+    /// NO shares sold by the pool and not bought back
+    pub q_no: u64,
+    /// Creator subsidy deposited when the pool was opened
+    pub subsidy: u64,
+    /// Whether the creator has withdrawn the subsidy or its leftover
+    pub subsidy_withdrawn: bool,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl LmsrPool {
+    pub const SEED: &'static [u8] = b"lmsr_pool";
+
+    /// Current value of the cost function
+    pub fn cost(&self) -> Result<u64> {
+        lmsr_cost(self.q_yes, self.q_no, self.liquidity)
+    }
+
+    /// Current YES probability in basis points
+    pub fn yes_price_bps(&self) -> Result<u64> {
+        lmsr_yes_price_bps(self.q_yes, self.q_no, self.liquidity)
+    }
+
+    /// Subsidy still held for the creator (excluded from cancellation refunds)
+    pub fn unwithdrawn_subsidy(&self) -> u64 {
+        if self.subsidy_withdrawn {
+            0
+        } else {
+            self.subsidy
+        }
+    }
+}
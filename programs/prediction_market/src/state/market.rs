@@ -1,11 +1,87 @@
 use anchor_lang::prelude::*;
 
-/// Market state enum
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+use crate::error::PredictionMarketError;
+use crate::merkle::{allowlist_leaf, verify_proof};
+use crate::state::{AllowedCreator, Credential, UserPosition};
+
+/// Market lifecycle state.
+///
+/// Only Active, Resolved, Disputed and Cancelled are ever stored; the other phases follow
+/// from the market's deadlines. See `crate::lifecycle` for the effective phase and the
+/// allowed transitions. New variants are appended so stored values keep their encoding.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
 pub enum MarketState {
+    /// Accepting bets and commits until the resolution time
     Active,
+    /// Outcome recorded; commits can be revealed and winnings claimed until the reveal deadline
     Resolved,
+    /// Settled as a refund; claims are open until claim expiry
     Cancelled,
+    /// Past the resolution time and waiting for the resolver (derived)
+    BettingClosed,
+    /// Resolution contested: payouts are frozen until the admin upholds or overturns it
+    Disputed,
+    /// Past the reveal deadline: unrevealed stakes can be forfeited, claims stay open (derived)
+    Settled,
+    /// Past claim expiry: the vault can be swept and accounts closed (derived)
+    Closed,
+}
+
+/// Why a market was cancelled
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum CancelReason {
+    /// Not cancelled
+    None,
+    /// Cancelled by the canceller role
+    Manual,
+    /// Converted to a refund at resolution because nobody took the other side
+    NoCounterparty,
+    /// Resolution overturned after a dispute
+    Overturned,
+    /// Withdrawn by its creator before anyone entered
+    Creator,
+    /// Emergency cancellation by the admin; see the market's cancel code
+    Emergency,
+}
+
+/// Creator bond lifecycle
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum BondStatus {
+    /// No bond posted (allowlisted creator)
+    None,
+    /// Bond held in the bond vault
+    Locked,
+    /// Bond sent to the protocol fee recipient
+    Slashed,
+    /// Bond returned to the creator
+    Released,
+}
+
+/// How a market prices and settles bets
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum MarketType {
+    /// Bets split the pools pro rata at settlement
+    PariMutuel,
+    /// Shares are bought from and sold to an LMSR market maker and pay 1:1
+    Lmsr,
+    /// Shares are traded against a constant-product pool funded by liquidity providers
+    Cpmm,
+    /// Commit-reveal bets paid at odds fixed when the market opened, from the house pool
+    FixedOdds,
+}
+
+/// How users enter a market. Chosen at creation and fixed for the market's lifetime,
+/// so the two settlement paths never share a vault.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum MarketMode {
+    /// Open bets and outcome shares: place_bet, complete sets, market makers and the
+    /// order book, settled through claim_winnings and redeem_shares
+    Public,
+    /// Hidden directions: stake_and_commit, settled through reveal_and_claim
+    Private,
+    /// Created before modes existed and may hold both kinds of deposit, so bets and
+    /// commits are both still accepted and settled. Only set by migrate_market.
+    Legacy,
 }
 
 /// Outcome enum
@@ -59,9 +135,317 @@ pub struct Market {
     /// Set to resolution_time + 2 weeks when market is resolved
     /// 0 if market not yet resolved
     pub reveal_deadline: i64,
+    /// Creator bond amount held in the bond vault (0 for allowlisted creators)
+    pub bond_amount: u64,
+    /// Creator bond status
+    pub bond_status: BondStatus,
+    /// Bond vault bump seed
+    pub bond_vault_bump: u8,
+    /// Per-market circuit breaker: freezes every user action on this market
+    pub paused: bool,
+    /// Account layout version
+    pub version: u8,
+    /// Number of user positions that have not been closed yet
+    pub open_positions: u32,
+    /// Claims close at this timestamp and the vault can be swept (0 until finalized)
+    pub claim_expiry: i64,
+    /// Whether the vault has been swept after claim expiry
+    pub swept: bool,
+    /// Total staked through stake_and_commit
+    pub total_committed: u64,
+    /// Total committed stake revealed as YES
+    pub total_revealed_yes: u64,
+    /// Total committed stake revealed as NO
+    pub total_revealed_no: u64,
+    /// Total paid out of the vault to users (winnings and refunds)
+    pub total_paid_out: u64,
+    /// Total committed stake that left the vault without a payout: unrevealed stakes
+    /// forfeited to the protocol, and losing or unrevealed fixed-odds stakes sent to the
+    /// house pool
+    pub total_forfeited: u64,
+    /// Total taken from the vault by the protocol (fees and sweeps)
+    pub total_fees: u64,
+    /// Number of users who committed through stake_and_commit
+    pub commit_count: u32,
+    /// Why the market was cancelled (None unless state is Cancelled)
+    pub cancel_reason: CancelReason,
+    /// Config snapshot - exit fee bps
+    pub exit_fee_bps: u16,
+    /// Number of positions with a YES bet
+    pub yes_bettors: u32,
+    /// Number of positions with a NO bet
+    pub no_bettors: u32,
+    /// YES share mint bump seed
+    pub yes_mint_bump: u8,
+    /// NO share mint bump seed
+    pub no_mint_bump: u8,
+    /// YES shares minted by place_bet and not yet exited
+    pub yes_bet_shares: u64,
+    /// NO shares minted by place_bet and not yet exited
+    pub no_bet_shares: u64,
+    /// Collateral backing complete sets (1 YES + 1 NO) that have not been redeemed,
+    /// including those held in constant-product pool reserves
+    pub complete_sets: u64,
+    /// Total paid out of the vault through redeem_shares
+    pub share_payouts: u64,
+    /// Pricing model
+    pub market_type: MarketType,
+    /// Collateral held by the LMSR market maker: creator subsidy plus trades net of sales
+    pub amm_collateral: u64,
+    /// Traders with resting orders or unsettled balances on the order book
+    pub book_traders: u32,
+    /// Smallest accepted place_bet or stake_and_commit amount (0 = no minimum)
+    pub min_bet: u64,
+    /// Most a single wallet may deposit through place_bet and stake_and_commit (0 = no cap)
+    pub max_bet_per_user: u64,
+    /// Cap on both pools plus committed stakes (0 = no cap)
+    pub max_total_pool: u64,
+    /// Merkle root of the wallets allowed to bet or commit (None = open to everyone)
+    pub allowlist_root: Option<[u8; 32]>,
+    /// Minimum credential tier required to enter the market (0 = no credential needed)
+    pub min_tier: u8,
+    /// Entry mode: public bets and shares, or private commit-reveal
+    pub mode: MarketMode,
+    /// When the current dispute was opened (0 unless Disputed)
+    pub disputed_at: i64,
+    /// Creation fee held in the bond vault until it is collected by the protocol or
+    /// refunded by a creator cancellation
+    pub fee_escrow: u64,
+    /// Admin-supplied reason code for an emergency cancellation (0 otherwise)
+    pub cancel_code: u16,
+    /// Exit fees kept from exit_position, paid to the winning side on resolution or to the
+    /// fee recipient on cancellation
+    pub exit_fees: u64,
+    /// Whether the market counts toward its allowlisted creator's open market limit
+    /// until it is resolved or cancelled
+    pub holds_creator_slot: bool,
+    /// Whether the resolution has been disputed; a market can only be disputed once
+    pub was_disputed: bool,
+    /// Disputes close, and payouts under the resolution open, at this timestamp (0 until
+    /// resolved)
+    pub dispute_deadline: i64,
+    /// Reserved for future fields; new fields are carved out of this padding
+    /// so existing accounts keep their size. Bump the version when it runs out.
+    pub reserved: [u8; 1],
 }
 
 impl Market {
     pub const SEED: &'static [u8] = b"market";
+    /// Current account layout version (0 = created before versioning)
+    pub const CURRENT_VERSION: u8 = 4;
     pub const VAULT_SEED: &'static [u8] = b"vault";
+    pub const BOND_VAULT_SEED: &'static [u8] = b"bond_vault";
+    pub const YES_MINT_SEED: &'static [u8] = b"yes_mint";
+    pub const NO_MINT_SEED: &'static [u8] = b"no_mint";
+
+    /// Whether every participant had someone to win from.
+    ///
+    /// Public pari-mutuel bets need bets on both sides (counted by legacy position or by
+    /// bet shares, since a pool can also hold legacy bets). Complete sets
+    /// take both sides at once and never count. In market maker markets the pool is
+    /// the counterparty to every trade. Private committed directions are hidden until
+    /// after resolution, so commit-reveal needs at least two committers, unless the house
+    /// takes the other side at fixed odds. A legacy market needs a counterparty in each
+    /// model it holds deposits in. A market without a counterparty is settled as a refund
+    /// for everyone.
+    pub fn has_counterparty(&self) -> bool {
+        let has_yes = self.yes_bettors > 0 || self.yes_bet_shares > 0;
+        let has_no = self.no_bettors > 0 || self.no_bet_shares > 0;
+        let bets_opposed = self.market_type != MarketType::PariMutuel || (has_yes && has_no);
+        let commits_opposed = self.commit_count > 1
+            || (self.commit_count > 0 && self.market_type == MarketType::FixedOdds);
+
+        match self.mode {
+            MarketMode::Public => bets_opposed,
+            MarketMode::Private => commits_opposed,
+            MarketMode::Legacy => {
+                // Bettor and commit counts started after some legacy markets opened, so
+                // fall back to the pools and the committed total. Commits made before they
+                // were counted cannot be told apart and are taken as opposed.
+                let has_yes = has_yes || self.yes_pool > 0;
+                let has_no = has_no || self.no_pool > 0;
+                let has_amm = self.market_type != MarketType::PariMutuel;
+                let has_bets = has_amm || has_yes || has_no;
+                let bets_opposed = has_amm || (has_yes && has_no);
+                let has_commits = self.commit_count > 0 || self.total_committed > 0;
+                let commits_opposed = commits_opposed || self.commit_count == 0;
+                (has_bets || has_commits)
+                    && (!has_bets || bets_opposed)
+                    && (!has_commits || commits_opposed)
+            }
+        }
+    }
+
+    /// Deposits in `position` that claim_winnings settles, as (bets, refundable
+    /// commitment). Each mode only settles its own deposits; commitments are only refunded
+    /// there on cancellation, since on resolution they go through reveal_and_claim.
+    pub fn claimable_deposits(&self, position: &UserPosition) -> Result<(u64, u64)> {
+        let bets = if self.mode == MarketMode::Private {
+            0
+        } else {
+            position
+                .yes_bet
+                .checked_add(position.no_bet)
+                .ok_or(PredictionMarketError::Overflow)?
+        };
+        let refundable_commitment = if self.mode != MarketMode::Public
+            && self.state == MarketState::Cancelled
+            && !position.revealed
+        {
+            position.committed_amount
+        } else {
+            0
+        };
+        Ok((bets, refundable_commitment))
+    }
+
+    /// Total deposited into the vault through place_bet, complete sets, the market maker
+    /// and stake_and_commit, net of exits, sales and complete set redemptions
+    pub fn total_deposits(&self) -> Result<u64> {
+        self.share_pot()?
+            .checked_add(self.total_committed)
+            .ok_or(PredictionMarketError::Overflow.into())
+    }
+
+    /// Whether nobody has bet, committed, traded or provided liquidity in the market.
+    /// `subsidy` is the creator's own unwithdrawn LMSR subsidy, which does not count.
+    /// Commits are checked by total as well as by count, since migrated legacy markets
+    /// only have the total.
+    pub fn is_untouched(&self, subsidy: u64) -> Result<bool> {
+        let deposits = self
+            .total_deposits()?
+            .checked_sub(subsidy)
+            .ok_or(PredictionMarketError::AccountingMismatch)?;
+        Ok(deposits == 0
+            && self.yes_bettors == 0
+            && self.no_bettors == 0
+            && self.commit_count == 0
+            && self.book_traders == 0)
+    }
+
+    /// Collateral backing YES and NO shares: both pools and their exit fees, outstanding
+    /// complete sets and the market maker's collateral
+    pub fn share_pot(&self) -> Result<u64> {
+        self.yes_pool
+            .checked_add(self.no_pool)
+            .and_then(|pools| pools.checked_add(self.exit_fees))
+            .and_then(|pools| pools.checked_add(self.complete_sets))
+            .and_then(|pot| pot.checked_add(self.amm_collateral))
+            .ok_or(PredictionMarketError::Overflow.into())
+    }
+
+    /// Share collateral not yet paid out through redeem_shares
+    pub fn share_pot_remaining(&self) -> Result<u64> {
+        self.share_pot()?
+            .checked_sub(self.share_payouts)
+            .ok_or(PredictionMarketError::AccountingMismatch.into())
+    }
+
+    /// Pari-mutuel payout for `stake` on the winning side: the stake plus its pro rata
+    /// share of the losing pool and the exit fees, rounded down. Legacy positions and bet
+    /// shares are both paid from the pools this way, so nothing else in the vault
+    /// dilutes them.
+    pub fn pari_mutuel_payout(&self, stake: u64) -> Result<u64> {
+        let (winning_pool, losing_pool) = match self.winning_outcome {
+            Outcome::Yes => (self.yes_pool, self.no_pool),
+            Outcome::No => (self.no_pool, self.yes_pool),
+            Outcome::None => return Ok(0),
+        };
+        let winnings = losing_pool
+            .checked_add(self.exit_fees)
+            .ok_or(PredictionMarketError::Overflow)?;
+        let share = (stake as u128)
+            .checked_mul(winnings as u128)
+            .ok_or(PredictionMarketError::Overflow)?
+            .checked_div(winning_pool as u128)
+            .ok_or(PredictionMarketError::Overflow)?;
+        stake
+            .checked_add(share as u64)
+            .ok_or(PredictionMarketError::Overflow.into())
+    }
+
+    /// Check that `wallet` may participate, given a Merkle proof for invite-only markets
+    pub fn check_allowlisted(&self, wallet: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+        if let Some(root) = self.allowlist_root {
+            require!(
+                verify_proof(proof, &root, allowlist_leaf(wallet)),
+                PredictionMarketError::NotAllowlisted
+            );
+        }
+        Ok(())
+    }
+
+    /// Check that a bettor's credential meets the market's minimum tier
+    pub fn check_credential(
+        &self,
+        credential: Option<&Credential>,
+        attester: &Pubkey,
+        now: i64,
+    ) -> Result<()> {
+        if self.min_tier == 0 {
+            return Ok(());
+        }
+        let tier = credential.map_or(0, |c| c.effective_tier(attester, now));
+        require!(tier >= self.min_tier, PredictionMarketError::CredentialRequired);
+        Ok(())
+    }
+
+    /// Check a deposit of `amount` against the market's stake limits and the protocol's
+    /// per-wallet cap, given what the wallet already deposited into this market
+    pub fn check_stake_limits(&self, amount: u64, user_staked: u64, wallet_cap: u64) -> Result<()> {
+        require!(amount >= self.min_bet, PredictionMarketError::BetBelowMinimum);
+
+        let user_total = user_staked
+            .checked_add(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+        for cap in [self.max_bet_per_user, wallet_cap] {
+            require!(
+                cap == 0 || user_total <= cap,
+                PredictionMarketError::UserStakeLimitExceeded
+            );
+        }
+
+        if self.max_total_pool > 0 {
+            let pool_total = self
+                .yes_pool
+                .checked_add(self.no_pool)
+                .and_then(|pools| pools.checked_add(self.total_committed))
+                .and_then(|pools| pools.checked_add(amount))
+                .ok_or(PredictionMarketError::Overflow)?;
+            require!(
+                pool_total <= self.max_total_pool,
+                PredictionMarketError::PoolLimitExceeded
+            );
+        }
+        Ok(())
+    }
+
+    /// What the vault still owes: every deposit not yet paid out, forfeited or taken as fees.
+    /// Losing commit-reveal stakes are counted until swept, so this is an upper bound.
+    pub fn outstanding_liabilities(&self) -> Result<u64> {
+        let outflows = self
+            .total_paid_out
+            .checked_add(self.total_forfeited)
+            .and_then(|outflows| outflows.checked_add(self.total_fees))
+            .ok_or(PredictionMarketError::Overflow)?;
+        self.total_deposits()?
+            .checked_sub(outflows)
+            .ok_or(PredictionMarketError::AccountingMismatch.into())
+    }
+
+    /// Free the creator's open market slot, if this market holds one. The entry must be
+    /// passed whenever it does, so the slot cannot be leaked by leaving it out.
+    pub fn release_creator_slot(
+        &mut self,
+        allowed_creator: Option<&mut AllowedCreator>,
+    ) -> Result<()> {
+        if !self.holds_creator_slot {
+            return Ok(());
+        }
+        let allowed_creator =
+            allowed_creator.ok_or(PredictionMarketError::CreatorEntryRequired)?;
+        allowed_creator.open_markets = allowed_creator.open_markets.saturating_sub(1);
+        self.holds_creator_slot = false;
+        Ok(())
+    }
 }
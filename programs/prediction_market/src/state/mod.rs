@@ -1,7 +1,29 @@
+pub mod allowed_creator;
 pub mod config;
+pub mod config_change;
+pub mod cpmm_pool;
+pub mod credential;
+pub mod fixed_odds;
+pub mod house_pool;
+pub mod lmsr_pool;
 pub mod market;
+pub mod order_book;
+#[cfg(test)]
+pub mod test_utils;
+pub mod user_limits;
 pub mod user_position;
+pub mod user_stake;
 
+pub use allowed_creator::*;
 pub use config::*;
+pub use config_change::*;
+pub use cpmm_pool::*;
+pub use credential::*;
+pub use fixed_odds::*;
+pub use house_pool::*;
+pub use lmsr_pool::*;
 pub use market::*;
+pub use order_book::*;
+pub use user_limits::*;
 pub use user_position::*;
+pub use user_stake::*;
@@ -0,0 +1,302 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::error::PredictionMarketError;
+
+/// Maximum number of resting orders on one book
+pub const MAX_BOOK_ORDERS: usize = 64;
+/// Maximum number of traders with open orders or unsettled balances on one book
+pub const MAX_BOOK_TRADERS: usize = 64;
+
+/// Order side, from the point of view of YES shares
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum Side {
+    /// Buy YES with collateral
+    Bid,
+    /// Sell YES for collateral
+    Ask,
+}
+
+impl Side {
+    /// How close an order at `price_bps` is to the top of this side of the book: the
+    /// bid price, or the complement of the ask price
+    pub fn price_priority(self, price_bps: u16) -> u64 {
+        match self {
+            Side::Bid => price_bps as u64,
+            Side::Ask => BPS_DENOMINATOR.saturating_sub(price_bps as u64),
+        }
+    }
+}
+
+/// Resting limit order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct Order {
+    /// Order ID, increasing in placement order (time priority)
+    pub id: u64,
+    /// Order owner
+    pub owner: Pubkey,
+    pub side: Side,
+    /// Limit price of one YES share in basis points of collateral
+    pub price_bps: u16,
+    /// YES shares still to fill
+    pub quantity: u64,
+    /// Escrow still held for the order: collateral for bids, YES shares for asks
+    pub escrow: u64,
+}
+
+impl Order {
+    pub fn price_priority(&self) -> u64 {
+        self.side.price_priority(self.price_bps)
+    }
+}
+
+/// Funds a trader can withdraw from the book through settle_funds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct TraderBalance {
+    /// Trader wallet
+    pub owner: Pubkey,
+    /// Collateral from fills, refunds and cancellations
+    pub collateral_free: u64,
+    /// YES shares from fills and cancellations
+    pub yes_free: u64,
+    /// Number of the trader's resting orders
+    pub open_orders: u16,
+}
+
+impl TraderBalance {
+    pub fn is_empty(&self) -> bool {
+        self.collateral_free == 0 && self.yes_free == 0 && self.open_orders == 0
+    }
+}
+
+/// Limit order book for a market's YES shares.
+///
+/// Orders live in a fixed-capacity slab and are matched by a permissionless crank in
+/// price-time priority. Fills are credited to trader balances, which are withdrawn from
+/// the book's escrow accounts through settle_funds.
+#[account]
+#[derive(InitSpace)]
+pub struct OrderBook {
+    /// Market ID
+    pub market_id: u64,
+    /// ID assigned to the next order
+    pub next_order_id: u64,
+    /// Resting orders (unsorted slab)
+    #[max_len(MAX_BOOK_ORDERS)]
+    pub orders: Vec<Order>,
+    /// Trader balances
+    #[max_len(MAX_BOOK_TRADERS)]
+    pub balances: Vec<TraderBalance>,
+    /// Collateral escrow bump seed
+    pub collateral_escrow_bump: u8,
+    /// YES escrow bump seed
+    pub yes_escrow_bump: u8,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl OrderBook {
+    pub const SEED: &'static [u8] = b"order_book";
+    pub const COLLATERAL_ESCROW_SEED: &'static [u8] = b"book_collateral";
+    pub const YES_ESCROW_SEED: &'static [u8] = b"book_yes";
+
+    /// Balance entry for `owner`, created if missing. Returns whether it was created.
+    pub fn balance_entry(&mut self, owner: Pubkey) -> Result<(&mut TraderBalance, bool)> {
+        match self.balances.iter().position(|b| b.owner == owner) {
+            Some(index) => Ok((&mut self.balances[index], false)),
+            None => {
+                require!(
+                    self.balances.len() < MAX_BOOK_TRADERS,
+                    PredictionMarketError::OrderBookFull
+                );
+                self.balances.push(TraderBalance {
+                    owner,
+                    collateral_free: 0,
+                    yes_free: 0,
+                    open_orders: 0,
+                });
+                let last = self.balances.len() - 1;
+                Ok((&mut self.balances[last], true))
+            }
+        }
+    }
+
+    /// Existing balance entry for `owner`
+    pub fn balance_mut(&mut self, owner: &Pubkey) -> Result<&mut TraderBalance> {
+        self.balances
+            .iter_mut()
+            .find(|b| b.owner == *owner)
+            .ok_or(PredictionMarketError::NoPosition.into())
+    }
+
+    /// Drop `owner`'s balance entry if nothing is left in it. Returns whether it was removed.
+    pub fn remove_balance_if_empty(&mut self, owner: &Pubkey) -> bool {
+        match self.balances.iter().position(|b| b.owner == *owner) {
+            Some(index) if self.balances[index].is_empty() => {
+                self.balances.swap_remove(index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove the order at `index` for its escrow to be paid out directly, without
+    /// crediting it to the owner's balance
+    pub fn take_order(&mut self, index: usize) -> Result<Order> {
+        let order = self.orders.swap_remove(index);
+        let balance = self.balance_mut(&order.owner)?;
+        balance.open_orders = balance.open_orders.saturating_sub(1);
+        Ok(order)
+    }
+
+    /// Remove the order at `index`, crediting its remaining escrow to the owner's balance
+    pub fn remove_order(&mut self, index: usize) -> Result<Order> {
+        let order = self.take_order(index)?;
+        let balance = self.balance_mut(&order.owner)?;
+        match order.side {
+            Side::Bid => {
+                balance.collateral_free = balance
+                    .collateral_free
+                    .checked_add(order.escrow)
+                    .ok_or(PredictionMarketError::Overflow)?;
+            }
+            Side::Ask => {
+                balance.yes_free = balance
+                    .yes_free
+                    .checked_add(order.escrow)
+                    .ok_or(PredictionMarketError::Overflow)?;
+            }
+        }
+        Ok(order)
+    }
+
+    /// Index of the order furthest from the top of the book on either side, latest
+    /// first on ties
+    pub fn worst_order(&self) -> Option<usize> {
+        self.orders
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.price_priority()
+                    .cmp(&b.price_priority())
+                    .then(b.id.cmp(&a.id))
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// Index of the highest bid, earliest first on ties
+    pub fn best_bid(&self) -> Option<usize> {
+        self.orders
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.side == Side::Bid)
+            .max_by(|(_, a), (_, b)| a.price_bps.cmp(&b.price_bps).then(b.id.cmp(&a.id)))
+            .map(|(index, _)| index)
+    }
+
+    /// Index of the lowest ask, earliest first on ties
+    pub fn best_ask(&self) -> Option<usize> {
+        self.orders
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.side == Side::Ask)
+            .min_by(|(_, a), (_, b)| a.price_bps.cmp(&b.price_bps).then(a.id.cmp(&b.id)))
+            .map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(id: u64, owner: Pubkey, side: Side, price_bps: u16) -> Order {
+        Order {
+            id,
+            owner,
+            side,
+            price_bps,
+            quantity: 10,
+            escrow: 10,
+        }
+    }
+
+    fn book(orders: Vec<Order>) -> OrderBook {
+        let mut book = OrderBook {
+            market_id: 1,
+            next_order_id: orders.len() as u64,
+            orders: Vec::new(),
+            balances: Vec::new(),
+            collateral_escrow_bump: 0,
+            yes_escrow_bump: 0,
+            bump: 0,
+        };
+        for order in orders {
+            book.balance_entry(order.owner).unwrap().0.open_orders += 1;
+            book.orders.push(order);
+        }
+        book
+    }
+
+    #[test]
+    fn worst_order_is_least_competitive_on_either_side() {
+        let trader = Pubkey::new_unique();
+        let book = book(vec![
+            order(0, trader, Side::Bid, 4_000),
+            order(1, trader, Side::Ask, 9_900),
+            order(2, trader, Side::Bid, 200),
+            order(3, trader, Side::Ask, 6_000),
+        ]);
+        // A 99% ask (100 from the top) is further out than a 2% bid (200)
+        assert_eq!(book.worst_order(), Some(1));
+    }
+
+    #[test]
+    fn worst_order_prefers_latest_on_ties() {
+        let trader = Pubkey::new_unique();
+        let book = book(vec![
+            order(0, trader, Side::Bid, 100),
+            order(1, trader, Side::Ask, 9_900),
+            order(2, trader, Side::Bid, 100),
+        ]);
+        assert_eq!(book.worst_order(), Some(2));
+    }
+
+    #[test]
+    fn removed_order_escrow_is_credited_to_its_owner() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut book = book(vec![
+            order(0, alice, Side::Bid, 5_000),
+            order(1, bob, Side::Ask, 6_000),
+        ]);
+
+        book.remove_order(1).unwrap();
+        let balance = *book.balance_mut(&bob).unwrap();
+        assert_eq!((balance.yes_free, balance.collateral_free), (10, 0));
+        assert_eq!(balance.open_orders, 0);
+
+        book.remove_order(0).unwrap();
+        let balance = *book.balance_mut(&alice).unwrap();
+        assert_eq!((balance.yes_free, balance.collateral_free), (0, 10));
+        assert!(book.orders.is_empty());
+    }
+
+    #[test]
+    fn taken_order_frees_its_owners_entry() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut book = book(vec![
+            order(0, alice, Side::Bid, 1),
+            order(1, alice, Side::Bid, 2),
+            order(2, bob, Side::Ask, 6_000),
+        ]);
+
+        // Nothing is credited, so the entry goes once the last order is taken
+        let taken = book.take_order(0).unwrap();
+        assert_eq!((taken.owner, taken.escrow), (alice, 10));
+        assert!(!book.remove_balance_if_empty(&alice));
+        // Bob's ask was swapped into the taken slot
+        book.take_order(1).unwrap();
+        assert!(book.remove_balance_if_empty(&alice));
+        assert!(book.balance_mut(&alice).is_err());
+        assert_eq!(book.balances.len(), 1);
+    }
+}
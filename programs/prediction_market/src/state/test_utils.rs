@@ -0,0 +1,6 @@
+use anchor_lang::prelude::*;
+
+/// An account deserialized from `len` zero bytes, with every field at its default
+pub fn zeroed<T: AnchorDeserialize>(len: usize) -> T {
+    T::deserialize(&mut &vec![0u8; len][..]).unwrap()
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{SECONDS_PER_DAY, SECONDS_PER_WEEK};
+use crate::error::PredictionMarketError;
+
+/// Deposit limits and self-exclusion a user has set for themselves.
+///
+/// Applies to every market. Tightening takes effect immediately; loosening a cap waits
+/// out a cooling-off period. Windows are calendar days and weeks in unix time.
+#[account]
+#[derive(InitSpace)]
+pub struct UserLimits {
+    /// User pubkey
+    pub user: Pubkey,
+    /// Most the user may deposit per day (0 = no cap)
+    pub daily_cap: u64,
+    /// Most the user may deposit per week (0 = no cap)
+    pub weekly_cap: u64,
+    /// Raised daily cap waiting out the cooling-off period
+    pub pending_daily_cap: Option<u64>,
+    /// Raised weekly cap waiting out the cooling-off period
+    pub pending_weekly_cap: Option<u64>,
+    /// When the pending caps take effect
+    pub pending_effective_at: i64,
+    /// No deposits are accepted before this timestamp
+    pub excluded_until: i64,
+    /// Day the daily total counts (unix time / 1 day)
+    pub day_index: i64,
+    /// Deposited during `day_index`
+    pub day_deposited: u64,
+    /// Week the weekly total counts (unix time / 1 week)
+    pub week_index: i64,
+    /// Deposited during `week_index`
+    pub week_deposited: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl UserLimits {
+    pub const SEED: &'static [u8] = b"limits";
+
+    /// Whether moving a cap from `current` to `new` loosens it (0 = no cap)
+    pub fn is_raise(current: u64, new: u64) -> bool {
+        current != 0 && (new == 0 || new > current)
+    }
+
+    /// Apply pending raises whose cooling-off period has passed
+    pub fn apply_due_changes(&mut self, now: i64) {
+        if now < self.pending_effective_at {
+            return;
+        }
+        if let Some(daily_cap) = self.pending_daily_cap.take() {
+            self.daily_cap = daily_cap;
+        }
+        if let Some(weekly_cap) = self.pending_weekly_cap.take() {
+            self.weekly_cap = weekly_cap;
+        }
+    }
+
+    /// Check a deposit against self-exclusion and the caps, and count it
+    pub fn record_deposit(&mut self, now: i64, amount: u64) -> Result<()> {
+        require!(now >= self.excluded_until, PredictionMarketError::SelfExcluded);
+        self.apply_due_changes(now);
+
+        let day_index = now / SECONDS_PER_DAY;
+        if day_index != self.day_index {
+            self.day_index = day_index;
+            self.day_deposited = 0;
+        }
+        let week_index = now / SECONDS_PER_WEEK;
+        if week_index != self.week_index {
+            self.week_index = week_index;
+            self.week_deposited = 0;
+        }
+
+        let day_deposited = self
+            .day_deposited
+            .checked_add(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+        let week_deposited = self
+            .week_deposited
+            .checked_add(amount)
+            .ok_or(PredictionMarketError::Overflow)?;
+        require!(
+            self.daily_cap == 0 || day_deposited <= self.daily_cap,
+            PredictionMarketError::DepositLimitExceeded
+        );
+        require!(
+            self.weekly_cap == 0 || week_deposited <= self.weekly_cap,
+            PredictionMarketError::DepositLimitExceeded
+        );
+        self.day_deposited = day_deposited;
+        self.week_deposited = week_deposited;
+        Ok(())
+    }
+}
+
+/// Enforce `user`'s limits, if they have set any, on a deposit of `amount`.
+///
+/// `limits` is the user's `UserLimits` PDA, already checked against its seeds. Users who
+/// never set limits have no account there.
+pub fn enforce_user_limits(limits: &AccountInfo, amount: u64) -> Result<()> {
+    if limits.data_is_empty() {
+        return Ok(());
+    }
+    require_keys_eq!(
+        *limits.owner,
+        crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    let mut account = UserLimits::try_deserialize(&mut &limits.try_borrow_data()?[..])?;
+    account.record_deposit(Clock::get()?.unix_timestamp, amount)?;
+    account.try_serialize(&mut &mut limits.try_borrow_mut_data()?[..])
+}
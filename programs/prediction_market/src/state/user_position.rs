@@ -10,9 +10,9 @@ pub struct UserPosition {
     pub market_id: u64,
     /// User pubkey
     pub user: Pubkey,
-    /// Amount bet on YES (legacy, unused in commit-reveal flow)
+    /// Amount bet on YES via place_bet before bets were minted as outcome shares
     pub yes_bet: u64,
-    /// Amount bet on NO (legacy, unused in commit-reveal flow)
+    /// Amount bet on NO via place_bet before bets were minted as outcome shares
     pub no_bet: u64,
     /// Has user claimed their payout
     pub claimed: bool,
@@ -26,8 +26,20 @@ pub struct UserPosition {
     pub revealed: bool,
     /// Revealed outcome for this commitment
     pub revealed_outcome: Outcome,
+    /// Account layout version
+    pub version: u8,
+    /// Reserved for future fields; new fields are carved out of this padding
+    /// so existing accounts keep their size. Bump the version when it runs out.
+    pub reserved: [u8; 32],
 }
 
 impl UserPosition {
     pub const SEED: &'static [u8] = b"position";
+    /// Current account layout version (0 = created before versioning)
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Whether nothing is staked in this position (e.g. after a full exit)
+    pub fn is_empty(&self) -> bool {
+        self.yes_bet == 0 && self.no_bet == 0 && self.committed_amount == 0
+    }
 }
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+/// Running total of what a wallet has deposited into one market, for stake limits.
+///
+/// Counts every place_bet and stake_and_commit deposit; exits do not give room back.
+#[account]
+#[derive(InitSpace)]
+pub struct UserStake {
+    /// Market ID
+    pub market_id: u64,
+    /// User pubkey
+    pub user: Pubkey,
+    /// Total deposited into the market
+    pub total_staked: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl UserStake {
+    pub const SEED: &'static [u8] = b"user_stake";
+}
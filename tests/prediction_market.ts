@@ -13,8 +13,11 @@ import {
   createAccount,
   mintTo,
   getAccount,
+  getAssociatedTokenAddressSync,
+  createAssociatedTokenAccountIdempotentInstruction,
 } from "@solana/spl-token";
 import { assert } from "chai";
+import { createHash, randomBytes } from "crypto";
 
 describe("prediction_market", () => {
   const provider = anchor.AnchorProvider.env();
@@ -60,7 +63,33 @@ describe("prediction_market", () => {
       [Buffer.from("vault"), marketIdBuffer],
       program.programId
     );
-    return { marketPda, marketVaultPda, marketIdBuffer };
+    const [yesMintPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("yes_mint"), marketIdBuffer],
+      program.programId
+    );
+    const [noMintPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("no_mint"), marketIdBuffer],
+      program.programId
+    );
+    const [bondVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bond_vault"), marketIdBuffer],
+      program.programId
+    );
+    return { marketPda, marketVaultPda, bondVaultPda, yesMintPda, noMintPda, marketIdBuffer };
+  };
+
+  const getUserLimitsPda = (user: PublicKey) => {
+    const [userLimitsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limits"), user.toBuffer()],
+      program.programId
+    );
+    return userLimitsPda;
+  };
+
+  const getShareAccount = (marketId: number, owner: PublicKey, outcome: { yes: {} } | { no: {} }) => {
+    const { yesMintPda, noMintPda } = getMarketPdas(marketId);
+    const mint = "yes" in outcome ? yesMintPda : noMintPda;
+    return { mint, shareAccount: getAssociatedTokenAddressSync(mint, owner) };
   };
 
   const getPositionPda = (marketId: number, user: PublicKey) => {
@@ -79,22 +108,34 @@ describe("prediction_market", () => {
     resolutionTimeSecs: number,
     creator: Keypair,
     creatorTokenAccount: PublicKey,
-    feeAmount: number = 0
+    feeAmount: number = 0,
+    mode: { public: {} } | { private: {} } = { public: {} },
+    priceThreshold: number = 100
   ): Promise<number> => {
     const nextMarketId = currentMarketId + 1;
-    const { marketPda, marketVaultPda } = getMarketPdas(nextMarketId);
+    const { marketPda, marketVaultPda, bondVaultPda, yesMintPda, noMintPda } =
+      getMarketPdas(nextMarketId);
     const resolutionTime = Math.floor(Date.now() / 1000) + resolutionTimeSecs;
 
     await program.methods
-      .createMarket(question, new anchor.BN(resolutionTime), new anchor.BN(feeAmount))
+      .createMarket(
+        question,
+        new anchor.BN(resolutionTime),
+        new anchor.BN(feeAmount),
+        new anchor.BN(priceThreshold),
+        mode
+      )
       .accounts({
         creator: creator.publicKey,
         config: configPda,
+        allowedCreator: null,
         market: marketPda,
         marketVault: marketVaultPda,
+        bondVault: bondVaultPda,
+        yesMint: yesMintPda,
+        noMint: noMintPda,
         tokenMint: tokenMint,
         creatorTokenAccount: creatorTokenAccount,
-        feeRecipientTokenAccount: feeRecipientTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -111,23 +152,28 @@ describe("prediction_market", () => {
     bettorTokenAccount: PublicKey,
     marketId: number,
     outcome: { yes: {} } | { no: {} },
-    amount: number
+    amount: number,
+    minPayoutMultiplierBps: number = 0
   ) => {
-    const { marketPda, marketVaultPda } = getMarketPdas(marketId);
-    const positionPda = getPositionPda(marketId, bettor.publicKey);
+    const { marketPda, marketVaultPda, yesMintPda, noMintPda } = getMarketPdas(marketId);
+    const { mint, shareAccount } = getShareAccount(marketId, bettor.publicKey, outcome);
 
     await program.methods
-      .placeBet(new anchor.BN(marketId), outcome, new anchor.BN(amount))
+      .placeBet(new anchor.BN(marketId), outcome, new anchor.BN(amount), new anchor.BN(minPayoutMultiplierBps), [])
       .accounts({
         bettor: bettor.publicKey,
         config: configPda,
         market: marketPda,
         marketVault: marketVaultPda,
-        userPosition: positionPda,
+        yesMint: yesMintPda,
+        noMint: noMintPda,
+        bettorShareAccount: shareAccount,
         bettorTokenAccount: bettorTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
-        systemProgram: SystemProgram.programId,
       })
+      .preInstructions([
+        createAssociatedTokenAccountIdempotentInstruction(bettor.publicKey, shareAccount, bettor.publicKey, mint),
+      ])
       .signers([bettor])
       .rpc();
   };
@@ -253,11 +299,12 @@ describe("prediction_market", () => {
     it("should initialize the program", async () => {
       const maxFeeBps = 500;
       await program.methods
-        .initialize(feeRecipient.publicKey, maxFeeBps)
+        .initialize(maxFeeBps)
         .accounts({
           admin: admin.publicKey,
           config: configPda,
           tokenMint: tokenMint,
+          feeRecipient: feeRecipient.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([admin])
@@ -269,7 +316,20 @@ describe("prediction_market", () => {
       assert.equal(config.tokenMint.toBase58(), tokenMint.toBase58());
       assert.equal(config.maxFeeBps, maxFeeBps);
       assert.equal(config.marketCounter.toNumber(), 0);
-      assert.equal(config.paused, false);
+      assert.equal(config.pauseFlags, 0);
+    });
+
+    it("should open market creation to bonded creators", async () => {
+      // No bond, so the test wallets can create markets without an allowlist entry
+      await program.methods
+        .setCreationPolicy(true, new anchor.BN(0))
+        .accounts({ authority: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+
+      const config = await program.account.config.fetch(configPda);
+      assert.equal(config.permissionlessCreation, true);
+      assert.equal(config.creatorBondAmount.toNumber(), 0);
     });
   });
 
@@ -294,7 +354,7 @@ describe("prediction_market", () => {
       assert.equal(market.noPool.toNumber(), 0);
     });
 
-    it("TC-MC-002: should create market with fee", async () => {
+    it("TC-MC-002: should create market with fee held in escrow", async () => {
       const fee = usdc(10);
       const feeRecipientBefore = (await getAccount(provider.connection, feeRecipientTokenAccount)).amount;
 
@@ -307,11 +367,12 @@ describe("prediction_market", () => {
       );
 
       const feeRecipientAfter = (await getAccount(provider.connection, feeRecipientTokenAccount)).amount;
-      assert.equal(feeRecipientAfter - feeRecipientBefore, BigInt(fee));
+      assert.equal(feeRecipientAfter, feeRecipientBefore);
 
       const { marketPda } = getMarketPdas(marketId);
       const market = await program.account.market.fetch(marketPda);
       assert.equal(market.creationFee.toNumber(), fee);
+      assert.equal(market.feeEscrow.toNumber(), fee);
     });
 
     it("should create multiple markets", async () => {
@@ -343,7 +404,6 @@ describe("prediction_market", () => {
             marketVault: marketVaultPda,
             tokenMint: tokenMint,
             creatorTokenAccount: aliceTokenAccount,
-            feeRecipientTokenAccount: feeRecipientTokenAccount,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
@@ -372,7 +432,6 @@ describe("prediction_market", () => {
             marketVault: marketVaultPda,
             tokenMint: tokenMint,
             creatorTokenAccount: aliceTokenAccount,
-            feeRecipientTokenAccount: feeRecipientTokenAccount,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
@@ -408,12 +467,11 @@ describe("prediction_market", () => {
       const { marketPda } = getMarketPdas(marketId);
       const market = await program.account.market.fetch(marketPda);
       assert.equal(market.yesPool.toNumber(), amount);
+      assert.equal(market.yesBetShares.toNumber(), amount);
 
-      const positionPda = getPositionPda(marketId, alice.publicKey);
-      const position = await program.account.userPosition.fetch(positionPda);
-      assert.equal(position.yesBet.toNumber(), amount);
-      assert.equal(position.noBet.toNumber(), 0);
-      assert.equal(position.claimed, false);
+      const { shareAccount } = getShareAccount(marketId, alice.publicKey, { yes: {} });
+      const shares = await getAccount(provider.connection, shareAccount);
+      assert.equal(shares.amount, BigInt(amount));
     });
 
     it("TC-BT-002: should place NO bet successfully", async () => {
@@ -424,10 +482,9 @@ describe("prediction_market", () => {
       const market = await program.account.market.fetch(marketPda);
       assert.equal(market.noPool.toNumber(), amount);
 
-      const positionPda = getPositionPda(marketId, bob.publicKey);
-      const position = await program.account.userPosition.fetch(positionPda);
-      assert.equal(position.yesBet.toNumber(), 0);
-      assert.equal(position.noBet.toNumber(), amount);
+      const { shareAccount } = getShareAccount(marketId, bob.publicKey, { no: {} });
+      const shares = await getAccount(provider.connection, shareAccount);
+      assert.equal(shares.amount, BigInt(amount));
     });
 
     it("TC-BT-003: should allow multiple bets same side", async () => {
@@ -436,9 +493,9 @@ describe("prediction_market", () => {
       await placeBet(alice, aliceTokenAccount, newMarketId, { yes: {} }, usdc(100));
       await placeBet(alice, aliceTokenAccount, newMarketId, { yes: {} }, usdc(50));
 
-      const positionPda = getPositionPda(newMarketId, alice.publicKey);
-      const position = await program.account.userPosition.fetch(positionPda);
-      assert.equal(position.yesBet.toNumber(), usdc(150));
+      const { shareAccount } = getShareAccount(newMarketId, alice.publicKey, { yes: {} });
+      const shares = await getAccount(provider.connection, shareAccount);
+      assert.equal(shares.amount, BigInt(usdc(150)));
     });
 
     it("TC-BT-004: should allow hedged bets (both sides)", async () => {
@@ -447,10 +504,16 @@ describe("prediction_market", () => {
       await placeBet(alice, aliceTokenAccount, newMarketId, { yes: {} }, usdc(100));
       await placeBet(alice, aliceTokenAccount, newMarketId, { no: {} }, usdc(50));
 
-      const positionPda = getPositionPda(newMarketId, alice.publicKey);
-      const position = await program.account.userPosition.fetch(positionPda);
-      assert.equal(position.yesBet.toNumber(), usdc(100));
-      assert.equal(position.noBet.toNumber(), usdc(50));
+      const yesShares = await getAccount(
+        provider.connection,
+        getShareAccount(newMarketId, alice.publicKey, { yes: {} }).shareAccount
+      );
+      const noShares = await getAccount(
+        provider.connection,
+        getShareAccount(newMarketId, alice.publicKey, { no: {} }).shareAccount
+      );
+      assert.equal(yesShares.amount, BigInt(usdc(100)));
+      assert.equal(noShares.amount, BigInt(usdc(50)));
     });
 
     it("should allow multiple users betting", async () => {
@@ -476,21 +539,22 @@ describe("prediction_market", () => {
     });
 
     it("TC-BT-006: should reject invalid outcome (None)", async () => {
-      const { marketPda, marketVaultPda } = getMarketPdas(marketId);
-      const positionPda = getPositionPda(marketId, alice.publicKey);
+      const { marketPda, marketVaultPda, yesMintPda, noMintPda } = getMarketPdas(marketId);
+      const { shareAccount } = getShareAccount(marketId, alice.publicKey, { yes: {} });
 
       try {
         await program.methods
-          .placeBet(new anchor.BN(marketId), { none: {} } as any, new anchor.BN(usdc(100)))
+          .placeBet(new anchor.BN(marketId), { none: {} } as any, new anchor.BN(usdc(100)), new anchor.BN(0), [])
           .accounts({
             bettor: alice.publicKey,
             config: configPda,
             market: marketPda,
             marketVault: marketVaultPda,
-            userPosition: positionPda,
+            yesMint: yesMintPda,
+            noMint: noMintPda,
+            bettorShareAccount: shareAccount,
             bettorTokenAccount: aliceTokenAccount,
             tokenProgram: TOKEN_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
           })
           .signers([alice])
           .rpc();
@@ -501,6 +565,55 @@ describe("prediction_market", () => {
       }
     });
 
+    it("should reject bet when payout multiplier is below minimum", async () => {
+      const newMarketId = await createMarket("Slippage test", 7 * 24 * 60 * 60, alice, aliceTokenAccount);
+      await placeBet(bob, bobTokenAccount, newMarketId, { no: {} }, usdc(100));
+      await placeBet(charlie, charlieTokenAccount, newMarketId, { yes: {} }, usdc(100));
+
+      // After a further 100 YES the pools are 200 YES / 100 NO, so YES pays 1.5x
+      try {
+        await placeBet(alice, aliceTokenAccount, newMarketId, { yes: {} }, usdc(100), 16_000);
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "SlippageExceeded");
+      }
+
+      await placeBet(alice, aliceTokenAccount, newMarketId, { yes: {} }, usdc(100), 15_000);
+    });
+
+    it("should enforce creator stake limits", async () => {
+      const newMarketId = await createMarket("Limits test", 7 * 24 * 60 * 60, alice, aliceTokenAccount);
+      const { marketPda } = getMarketPdas(newMarketId);
+      await program.methods
+        .setMarketLimits(new anchor.BN(newMarketId), new anchor.BN(usdc(10)), new anchor.BN(usdc(150)), new anchor.BN(usdc(250)))
+        .accounts({ creator: alice.publicKey, market: marketPda })
+        .signers([alice])
+        .rpc();
+
+      try {
+        await placeBet(bob, bobTokenAccount, newMarketId, { yes: {} }, usdc(5));
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "BetBelowMinimum");
+      }
+
+      await placeBet(bob, bobTokenAccount, newMarketId, { yes: {} }, usdc(100));
+      try {
+        await placeBet(bob, bobTokenAccount, newMarketId, { no: {} }, usdc(100));
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "UserStakeLimitExceeded");
+      }
+
+      await placeBet(charlie, charlieTokenAccount, newMarketId, { no: {} }, usdc(100));
+      try {
+        await placeBet(alice, aliceTokenAccount, newMarketId, { no: {} }, usdc(100));
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "PoolLimitExceeded");
+      }
+    });
+
     it("TC-BT-013: should reject when paused", async () => {
       await program.methods.pause().accounts({ admin: admin.publicKey, config: configPda }).signers([admin]).rpc();
 
@@ -662,6 +775,60 @@ describe("prediction_market", () => {
       }
     });
 
+    it("should let the creator withdraw an empty market and refund the fee", async () => {
+      const fee = usdc(5);
+      const marketId = await createMarket("Creator cancel", 3600, alice, aliceTokenAccount, fee);
+      const { marketPda } = getMarketPdas(marketId);
+      const balanceBefore = (await getAccount(provider.connection, aliceTokenAccount)).amount;
+
+      await program.methods
+        .creatorCancelMarket(new anchor.BN(marketId))
+        .accounts({ creator: alice.publicKey, market: marketPda, creatorTokenAccount: aliceTokenAccount })
+        .signers([alice])
+        .rpc();
+
+      const balanceAfter = (await getAccount(provider.connection, aliceTokenAccount)).amount;
+      assert.equal(balanceAfter - balanceBefore, BigInt(fee));
+      const market = await program.account.market.fetch(marketPda);
+      assert.deepEqual(market.state, { cancelled: {} });
+      assert.deepEqual(market.cancelReason, { creator: {} });
+      assert.equal(market.feeEscrow.toNumber(), 0);
+    });
+
+    it("should reject creator cancellation once bets exist", async () => {
+      const marketId = await createMarket("Creator cancel with bets", 3600, alice, aliceTokenAccount);
+      await placeBet(bob, bobTokenAccount, marketId, { yes: {} }, usdc(10));
+
+      const { marketPda } = getMarketPdas(marketId);
+      try {
+        await program.methods
+          .creatorCancelMarket(new anchor.BN(marketId))
+          .accounts({ creator: alice.publicKey, market: marketPda, creatorTokenAccount: aliceTokenAccount })
+          .signers([alice])
+          .rpc();
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "MarketNotEmpty");
+      }
+    });
+
+    it("should emergency cancel before resolution time with a reason code", async () => {
+      const marketId = await createMarket("Emergency cancel", 3600, alice, aliceTokenAccount);
+      await placeBet(alice, aliceTokenAccount, marketId, { yes: {} }, usdc(100));
+
+      const { marketPda } = getMarketPdas(marketId);
+      await program.methods
+        .emergencyCancelMarket(new anchor.BN(marketId), 7)
+        .accounts({ admin: admin.publicKey, config: configPda, market: marketPda })
+        .signers([admin])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketPda);
+      assert.deepEqual(market.state, { cancelled: {} });
+      assert.deepEqual(market.cancelReason, { emergency: {} });
+      assert.equal(market.cancelCode, 7);
+    });
+
     it("TC-CN-003: should reject non-admin cancellation", async () => {
       const marketId = await createMarket("Non-admin cancel", 2, alice, aliceTokenAccount);
       await placeBet(alice, aliceTokenAccount, marketId, { yes: {} }, usdc(100));
@@ -945,6 +1112,692 @@ describe("prediction_market", () => {
     });
   });
 
+  // ============ Constant-Product Market Tests (TC-CP-*) ============
+  describe("Constant-Product Market", () => {
+    const FEE_BPS = 100;
+    let marketId: number;
+
+    const getCpmmPdas = (marketId: number) => {
+      const { marketIdBuffer } = getMarketPdas(marketId);
+      const pda = (seed: string) =>
+        PublicKey.findProgramAddressSync([Buffer.from(seed), marketIdBuffer], program.programId)[0];
+      return {
+        cpmmPoolPda: pda("cpmm_pool"),
+        lpMintPda: pda("lp_mint"),
+        yesReservePda: pda("cpmm_yes"),
+        noReservePda: pda("cpmm_no"),
+      };
+    };
+
+    const getReserves = async (outcome: { yes: {} } | { no: {} }) => {
+      const { yesReservePda, noReservePda } = getCpmmPdas(marketId);
+      const yes = (await getAccount(provider.connection, yesReservePda)).amount;
+      const no = (await getAccount(provider.connection, noReservePda)).amount;
+      return "yes" in outcome ? { own: yes, other: no } : { own: no, other: yes };
+    };
+
+    // Mirrors math::cpmm_shares_out: the pool keeps the rounding
+    const quoteBuy = (own: bigint, other: bigint, investment: number) => {
+      const net = BigInt(investment - Math.floor((investment * FEE_BPS) / 10_000));
+      const otherAfter = other + net;
+      const ownAfter = (own * other + otherAfter - BigInt(1)) / otherAfter;
+      return own + net - ownAfter;
+    };
+
+    // Mirrors math::cpmm_shares_in, with the fee added to the return first
+    const quoteSell = (own: bigint, other: bigint, returnAmount: number) => {
+      const gross = BigInt(Math.ceil((returnAmount * 10_000) / (10_000 - FEE_BPS)));
+      const otherAfter = other - gross;
+      const ownAfter = (own * other + otherAfter - BigInt(1)) / otherAfter;
+      return ownAfter + gross - own;
+    };
+
+    const tradeAccounts = (trader: Keypair, traderTokenAccount: PublicKey, outcome: { yes: {} } | { no: {} }) => {
+      const { marketPda, marketVaultPda, yesMintPda, noMintPda } = getMarketPdas(marketId);
+      const { cpmmPoolPda, yesReservePda, noReservePda } = getCpmmPdas(marketId);
+      const { shareAccount } = getShareAccount(marketId, trader.publicKey, outcome);
+      return {
+        trader: trader.publicKey,
+        config: configPda,
+        market: marketPda,
+        marketVault: marketVaultPda,
+        cpmmPool: cpmmPoolPda,
+        yesMint: yesMintPda,
+        noMint: noMintPda,
+        yesReserve: yesReservePda,
+        noReserve: noReservePda,
+        traderShareAccount: shareAccount,
+        traderTokenAccount: traderTokenAccount,
+        userLimits: getUserLimitsPda(trader.publicKey),
+        credential: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+    };
+
+    const buyShares = async (
+      trader: Keypair,
+      traderTokenAccount: PublicKey,
+      outcome: { yes: {} } | { no: {} },
+      investment: number,
+      minSharesOut: bigint
+    ) => {
+      const { mint, shareAccount } = getShareAccount(marketId, trader.publicKey, outcome);
+      await program.methods
+        .buyCpmmShares(
+          new anchor.BN(marketId),
+          outcome,
+          new anchor.BN(investment),
+          new anchor.BN(minSharesOut.toString()),
+          []
+        )
+        .accounts(tradeAccounts(trader, traderTokenAccount, outcome))
+        .preInstructions([
+          createAssociatedTokenAccountIdempotentInstruction(trader.publicKey, shareAccount, trader.publicKey, mint),
+        ])
+        .signers([trader])
+        .rpc();
+    };
+
+    const sellShares = async (
+      trader: Keypair,
+      traderTokenAccount: PublicKey,
+      outcome: { yes: {} } | { no: {} },
+      returnAmount: number,
+      maxSharesIn: bigint
+    ) => {
+      await program.methods
+        .sellCpmmShares(
+          new anchor.BN(marketId),
+          outcome,
+          new anchor.BN(returnAmount),
+          new anchor.BN(maxSharesIn.toString())
+        )
+        .accounts(tradeAccounts(trader, traderTokenAccount, outcome))
+        .signers([trader])
+        .rpc();
+    };
+
+    before(async () => {
+      marketId = await createMarket("CPMM test market", 7 * 24 * 60 * 60, alice, aliceTokenAccount);
+      const { marketPda, yesMintPda, noMintPda } = getMarketPdas(marketId);
+      const { cpmmPoolPda, lpMintPda, yesReservePda, noReservePda } = getCpmmPdas(marketId);
+
+      await program.methods
+        .initCpmmPool(new anchor.BN(marketId), FEE_BPS)
+        .accounts({
+          creator: alice.publicKey,
+          config: configPda,
+          market: marketPda,
+          cpmmPool: cpmmPoolPda,
+          lpMint: lpMintPda,
+          yesMint: yesMintPda,
+          noMint: noMintPda,
+          yesReserve: yesReservePda,
+          noReserve: noReservePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([alice])
+        .rpc();
+    });
+
+    it("TC-CP-001: should add liquidity at even odds for LP tokens", async () => {
+      const { marketPda, marketVaultPda, yesMintPda, noMintPda } = getMarketPdas(marketId);
+      const { cpmmPoolPda, lpMintPda, yesReservePda, noReservePda } = getCpmmPdas(marketId);
+      const providerLpAccount = getAssociatedTokenAddressSync(lpMintPda, alice.publicKey);
+      const providerYesAccount = getAssociatedTokenAddressSync(yesMintPda, alice.publicKey);
+      const providerNoAccount = getAssociatedTokenAddressSync(noMintPda, alice.publicKey);
+
+      await program.methods
+        .addLiquidity(new anchor.BN(marketId), new anchor.BN(usdc(1000)), [])
+        .accounts({
+          provider: alice.publicKey,
+          config: configPda,
+          market: marketPda,
+          marketVault: marketVaultPda,
+          cpmmPool: cpmmPoolPda,
+          yesMint: yesMintPda,
+          noMint: noMintPda,
+          lpMint: lpMintPda,
+          yesReserve: yesReservePda,
+          noReserve: noReservePda,
+          providerLpAccount: providerLpAccount,
+          providerYesAccount: providerYesAccount,
+          providerNoAccount: providerNoAccount,
+          providerTokenAccount: aliceTokenAccount,
+          userLimits: getUserLimitsPda(alice.publicKey),
+          credential: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .preInstructions([
+          createAssociatedTokenAccountIdempotentInstruction(alice.publicKey, providerLpAccount, alice.publicKey, lpMintPda),
+          createAssociatedTokenAccountIdempotentInstruction(alice.publicKey, providerYesAccount, alice.publicKey, yesMintPda),
+          createAssociatedTokenAccountIdempotentInstruction(alice.publicKey, providerNoAccount, alice.publicKey, noMintPda),
+        ])
+        .signers([alice])
+        .rpc();
+
+      const { own, other } = await getReserves({ yes: {} });
+      assert.equal(own, BigInt(usdc(1000)));
+      assert.equal(other, BigInt(usdc(1000)));
+      assert.equal((await getAccount(provider.connection, providerLpAccount)).amount, BigInt(usdc(1000)));
+
+      const market = await program.account.market.fetch(marketPda);
+      assert.deepEqual(market.marketType, { cpmm: {} });
+    });
+
+    it("TC-CP-002: should buy shares at the quoted price", async () => {
+      const investment = usdc(100);
+      const { own, other } = await getReserves({ yes: {} });
+      const quote = quoteBuy(own, other, investment);
+      const { shareAccount } = getShareAccount(marketId, bob.publicKey, { yes: {} });
+      const bobBalanceBefore = (await getAccount(provider.connection, bobTokenAccount)).amount;
+
+      await buyShares(bob, bobTokenAccount, { yes: {} }, investment, quote);
+
+      const bobBalanceAfter = (await getAccount(provider.connection, bobTokenAccount)).amount;
+      assert.equal(bobBalanceBefore - bobBalanceAfter, BigInt(investment));
+      assert.equal((await getAccount(provider.connection, shareAccount)).amount, quote);
+
+      // YES got more expensive: more than one share per unit, fewer than two
+      assert.isTrue(quote > BigInt(investment) && quote < BigInt(2 * investment));
+      const after = await getReserves({ yes: {} });
+      assert.isTrue(after.own < after.other);
+    });
+
+    it("TC-CP-003: should reject a buy below the minimum shares out", async () => {
+      const investment = usdc(100);
+      const { own, other } = await getReserves({ no: {} });
+      const quote = quoteBuy(own, other, investment);
+      const charlieBalanceBefore = (await getAccount(provider.connection, charlieTokenAccount)).amount;
+
+      try {
+        await buyShares(charlie, charlieTokenAccount, { no: {} }, investment, quote + BigInt(1));
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "TradeLimitExceeded");
+      }
+
+      const charlieBalanceAfter = (await getAccount(provider.connection, charlieTokenAccount)).amount;
+      assert.equal(charlieBalanceAfter, charlieBalanceBefore);
+    });
+
+    it("TC-CP-004: should sell shares back within the maximum shares in", async () => {
+      const returnAmount = usdc(50);
+      const { own, other } = await getReserves({ yes: {} });
+      const quote = quoteSell(own, other, returnAmount);
+      const { shareAccount } = getShareAccount(marketId, bob.publicKey, { yes: {} });
+      const sharesBefore = (await getAccount(provider.connection, shareAccount)).amount;
+      const bobBalanceBefore = (await getAccount(provider.connection, bobTokenAccount)).amount;
+
+      await sellShares(bob, bobTokenAccount, { yes: {} }, returnAmount, quote);
+
+      const bobBalanceAfter = (await getAccount(provider.connection, bobTokenAccount)).amount;
+      assert.equal(bobBalanceAfter - bobBalanceBefore, BigInt(returnAmount));
+      assert.equal(sharesBefore - (await getAccount(provider.connection, shareAccount)).amount, quote);
+    });
+
+    it("TC-CP-005: should reject a sell needing more than the maximum shares in", async () => {
+      const returnAmount = usdc(20);
+      const { own, other } = await getReserves({ yes: {} });
+      const quote = quoteSell(own, other, returnAmount);
+      const { shareAccount } = getShareAccount(marketId, bob.publicKey, { yes: {} });
+      const sharesBefore = (await getAccount(provider.connection, shareAccount)).amount;
+
+      try {
+        await sellShares(bob, bobTokenAccount, { yes: {} }, returnAmount, quote - BigInt(1));
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "TradeLimitExceeded");
+      }
+
+      assert.equal((await getAccount(provider.connection, shareAccount)).amount, sharesBefore);
+    });
+  });
+
+  // ============ Order Book Tests (TC-OB-*) ============
+  describe("Order Book", () => {
+    const MAX_BOOK_ORDERS = 64;
+    let marketId: number;
+
+    const getBookPdas = (marketId: number) => {
+      const { marketIdBuffer } = getMarketPdas(marketId);
+      const pda = (seed: string) =>
+        PublicKey.findProgramAddressSync([Buffer.from(seed), marketIdBuffer], program.programId)[0];
+      return {
+        orderBookPda: pda("order_book"),
+        collateralEscrowPda: pda("book_collateral"),
+        yesEscrowPda: pda("book_yes"),
+      };
+    };
+
+    const fetchBook = () => program.account.orderBook.fetch(getBookPdas(marketId).orderBookPda);
+
+    const placeOrder = async (
+      trader: Keypair,
+      traderTokenAccount: PublicKey,
+      side: { bid: {} } | { ask: {} },
+      priceBps: number,
+      quantity: number,
+      evictedOwnerAccount: PublicKey | null = null
+    ) => {
+      const { marketPda, yesMintPda } = getMarketPdas(marketId);
+      const { orderBookPda, collateralEscrowPda, yesEscrowPda } = getBookPdas(marketId);
+      const traderYesAccount = getAssociatedTokenAddressSync(yesMintPda, trader.publicKey);
+
+      await program.methods
+        .placeOrder(new anchor.BN(marketId), side, priceBps, new anchor.BN(quantity), [])
+        .accounts({
+          trader: trader.publicKey,
+          config: configPda,
+          market: marketPda,
+          orderBook: orderBookPda,
+          collateralEscrow: collateralEscrowPda,
+          yesEscrow: yesEscrowPda,
+          traderTokenAccount: traderTokenAccount,
+          traderYesAccount: traderYesAccount,
+          userLimits: getUserLimitsPda(trader.publicKey),
+          credential: null,
+          evictedOwnerAccount: evictedOwnerAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .preInstructions([
+          createAssociatedTokenAccountIdempotentInstruction(trader.publicKey, traderYesAccount, trader.publicKey, yesMintPda),
+        ])
+        .signers([trader])
+        .rpc();
+    };
+
+    before(async () => {
+      marketId = await createMarket("Order book test market", 7 * 24 * 60 * 60, alice, aliceTokenAccount);
+      const { marketPda, yesMintPda } = getMarketPdas(marketId);
+      const { orderBookPda, collateralEscrowPda, yesEscrowPda } = getBookPdas(marketId);
+
+      await program.methods
+        .initOrderBook(new anchor.BN(marketId))
+        .accounts({
+          payer: alice.publicKey,
+          config: configPda,
+          market: marketPda,
+          orderBook: orderBookPda,
+          tokenMint: tokenMint,
+          yesMint: yesMintPda,
+          collateralEscrow: collateralEscrowPda,
+          yesEscrow: yesEscrowPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([alice])
+        .rpc();
+    });
+
+    it("TC-OB-001: should place a bid and escrow its collateral", async () => {
+      const aliceBalanceBefore = (await getAccount(provider.connection, aliceTokenAccount)).amount;
+
+      // Five YES shares at 40%
+      await placeOrder(alice, aliceTokenAccount, { bid: {} }, 4_000, usdc(5));
+
+      const aliceBalanceAfter = (await getAccount(provider.connection, aliceTokenAccount)).amount;
+      assert.equal(aliceBalanceBefore - aliceBalanceAfter, BigInt(usdc(2)));
+      const { collateralEscrowPda } = getBookPdas(marketId);
+      assert.equal((await getAccount(provider.connection, collateralEscrowPda)).amount, BigInt(usdc(2)));
+
+      const book = await fetchBook();
+      assert.equal(book.orders.length, 1);
+      assert.equal(book.orders[0].owner.toBase58(), alice.publicKey.toBase58());
+      assert.equal(book.orders[0].priceBps, 4_000);
+      assert.equal(book.orders[0].escrow.toNumber(), usdc(2));
+    });
+
+    it("TC-OB-002: should reject an order below one whole share", async () => {
+      try {
+        await placeOrder(alice, aliceTokenAccount, { bid: {} }, 4_000, usdc(1) - 1);
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "OrderTooSmall");
+      }
+    });
+
+    it("TC-OB-003: should reject an order worth less than one whole unit of collateral", async () => {
+      // 99 shares at 1% are worth 0.99
+      try {
+        await placeOrder(alice, aliceTokenAccount, { bid: {} }, 100, usdc(99));
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "OrderTooSmall");
+      }
+    });
+
+    it("TC-OB-004: should refund a cancelled order and drop its owner's balance entry", async () => {
+      const { marketPda, yesMintPda } = getMarketPdas(marketId);
+      const { orderBookPda, collateralEscrowPda, yesEscrowPda } = getBookPdas(marketId);
+      await placeOrder(bob, bobTokenAccount, { bid: {} }, 5_000, usdc(2));
+      const order = (await fetchBook()).orders.find(
+        (order) => order.owner.toBase58() === bob.publicKey.toBase58()
+      );
+      const bobBalanceBefore = (await getAccount(provider.connection, bobTokenAccount)).amount;
+
+      await program.methods
+        .cancelOrder(new anchor.BN(marketId), order.id)
+        .accounts({
+          trader: bob.publicKey,
+          config: configPda,
+          market: marketPda,
+          orderBook: orderBookPda,
+          collateralEscrow: collateralEscrowPda,
+          yesEscrow: yesEscrowPda,
+          traderTokenAccount: bobTokenAccount,
+          traderYesAccount: getAssociatedTokenAddressSync(yesMintPda, bob.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bob])
+        .rpc();
+
+      const bobBalanceAfter = (await getAccount(provider.connection, bobTokenAccount)).amount;
+      assert.equal(bobBalanceAfter - bobBalanceBefore, BigInt(usdc(1)));
+      const book = await fetchBook();
+      assert.equal(book.orders.length, 1);
+      assert.isUndefined(
+        book.balances.find((balance) => balance.owner.toBase58() === bob.publicKey.toBase58())
+      );
+    });
+
+    it("TC-OB-005: should reject a less competitive order when the book is full", async () => {
+      const book = await fetchBook();
+      for (let i = book.orders.length; i < MAX_BOOK_ORDERS; i++) {
+        await placeOrder(charlie, charlieTokenAccount, { bid: {} }, 100, usdc(100));
+      }
+      assert.equal((await fetchBook()).orders.length, MAX_BOOK_ORDERS);
+
+      try {
+        await placeOrder(bob, bobTokenAccount, { bid: {} }, 50, usdc(200), charlieTokenAccount);
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "OrderBookFull");
+      }
+
+      // Matching the worst price is not enough either
+      try {
+        await placeOrder(bob, bobTokenAccount, { bid: {} }, 100, usdc(100), charlieTokenAccount);
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "OrderBookFull");
+      }
+    });
+
+    it("TC-OB-006: should evict the least competitive order for a better one", async () => {
+      const countAt = (orders: { priceBps: number }[], priceBps: number) =>
+        orders.filter((order) => order.priceBps === priceBps).length;
+      const before = await fetchBook();
+
+      // The evicted escrow must go to its owner's account
+      try {
+        await placeOrder(bob, bobTokenAccount, { bid: {} }, 200, usdc(50), bobTokenAccount);
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "EvictedOwnerAccountMismatch");
+      }
+
+      const charlieTokensBefore = (await getAccount(provider.connection, charlieTokenAccount)).amount;
+      await placeOrder(bob, bobTokenAccount, { bid: {} }, 200, usdc(50), charlieTokenAccount);
+
+      const after = await fetchBook();
+      assert.equal(after.orders.length, MAX_BOOK_ORDERS);
+      assert.equal(countAt(after.orders, 100), countAt(before.orders, 100) - 1);
+      assert.equal(countAt(after.orders, 200), 1);
+
+      // The evicted escrow is paid straight back to its owner, not left in the book
+      const charlieTokensAfter = (await getAccount(provider.connection, charlieTokenAccount)).amount;
+      assert.equal(charlieTokensAfter - charlieTokensBefore, BigInt(usdc(1)));
+      const charlieBalance = after.balances.find(
+        (balance) => balance.owner.toBase58() === charlie.publicKey.toBase58()
+      );
+      assert.equal(charlieBalance.collateralFree.toNumber(), 0);
+      assert.equal(charlieBalance.openOrders, MAX_BOOK_ORDERS - 2);
+    });
+  });
+
+  // ============ Fixed-Odds Market Tests (TC-FO-*) ============
+  describe("Fixed-Odds Market", () => {
+    const ODDS_BPS = 20_000;
+    const MAX_LIABILITY = usdc(100);
+    let marketId: number;
+
+    const [housePoolPda] = PublicKey.findProgramAddressSync([Buffer.from("house_pool")], program.programId);
+    const [houseVaultPda] = PublicKey.findProgramAddressSync([Buffer.from("house_vault")], program.programId);
+    const [houseLpMintPda] = PublicKey.findProgramAddressSync([Buffer.from("house_lp_mint")], program.programId);
+
+    const getFixedOddsPda = (marketId: number) => {
+      const { marketIdBuffer } = getMarketPdas(marketId);
+      const [fixedOddsPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("fixed_odds"), marketIdBuffer],
+        program.programId
+      );
+      return fixedOddsPda;
+    };
+
+    const stakeAndCommit = async (
+      bettor: Keypair,
+      bettorTokenAccount: PublicKey,
+      outcome: { yes: {} } | { no: {} },
+      amount: number
+    ) => {
+      const { marketPda, marketVaultPda, marketIdBuffer } = getMarketPdas(marketId);
+      const [userStakePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("user_stake"), marketIdBuffer, bettor.publicKey.toBuffer()],
+        program.programId
+      );
+      // SHA256(market_id || user_pubkey || outcome_discriminant || salt)
+      const commitment = createHash("sha256")
+        .update(marketIdBuffer)
+        .update(bettor.publicKey.toBuffer())
+        .update(Buffer.from(["yes" in outcome ? 1 : 2]))
+        .update(randomBytes(32))
+        .digest();
+
+      await program.methods
+        .stakeAndCommit(new anchor.BN(marketId), new anchor.BN(amount), Array.from(commitment), [])
+        .accounts({
+          bettor: bettor.publicKey,
+          config: configPda,
+          market: marketPda,
+          marketVault: marketVaultPda,
+          userPosition: getPositionPda(marketId, bettor.publicKey),
+          userStake: userStakePda,
+          userLimits: getUserLimitsPda(bettor.publicKey),
+          credential: null,
+          bettorTokenAccount: bettorTokenAccount,
+          fixedOdds: getFixedOddsPda(marketId),
+          housePool: housePoolPda,
+          houseVault: houseVaultPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor])
+        .rpc();
+    };
+
+    before(async () => {
+      await program.methods
+        .initHousePool()
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+          housePool: housePoolPda,
+          tokenMint: tokenMint,
+          houseVault: houseVaultPda,
+          lpMint: houseLpMintPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      const providerLpAccount = getAssociatedTokenAddressSync(houseLpMintPda, alice.publicKey);
+      await program.methods
+        .depositHouseLiquidity(new anchor.BN(usdc(1000)))
+        .accounts({
+          provider: alice.publicKey,
+          config: configPda,
+          housePool: housePoolPda,
+          houseVault: houseVaultPda,
+          lpMint: houseLpMintPda,
+          providerLpAccount: providerLpAccount,
+          providerTokenAccount: aliceTokenAccount,
+          userLimits: getUserLimitsPda(alice.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .preInstructions([
+          createAssociatedTokenAccountIdempotentInstruction(alice.publicKey, providerLpAccount, alice.publicKey, houseLpMintPda),
+        ])
+        .signers([alice])
+        .rpc();
+
+      marketId = await createMarket(
+        "Fixed-odds test market",
+        7 * 24 * 60 * 60,
+        alice,
+        aliceTokenAccount,
+        0,
+        { private: {} }
+      );
+    });
+
+    it("TC-FO-001: should open a fixed-odds market co-signed by the creator manager", async () => {
+      const { marketPda } = getMarketPdas(marketId);
+      await program.methods
+        .initFixedOdds(new anchor.BN(marketId), ODDS_BPS, new anchor.BN(MAX_LIABILITY))
+        .accounts({
+          creator: alice.publicKey,
+          approver: admin.publicKey,
+          config: configPda,
+          market: marketPda,
+          fixedOdds: getFixedOddsPda(marketId),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([alice, admin])
+        .rpc();
+
+      const market = await program.account.market.fetch(marketPda);
+      assert.deepEqual(market.marketType, { fixedOdds: {} });
+      const terms = await program.account.fixedOddsTerms.fetch(getFixedOddsPda(marketId));
+      assert.equal(terms.oddsBps, ODDS_BPS);
+      assert.equal(terms.maxLiability.toNumber(), MAX_LIABILITY);
+      assert.equal(terms.reservedLiability.toNumber(), 0);
+    });
+
+    it("TC-FO-002: should reserve the worst-case payout of a commit", async () => {
+      const reservedBefore = (await program.account.housePool.fetch(housePoolPda)).totalReserved;
+
+      await stakeAndCommit(bob, bobTokenAccount, { yes: {} }, usdc(60));
+
+      // Directions are hidden, so the commit is assumed to win: 60 * (2x - 1) = 60
+      const terms = await program.account.fixedOddsTerms.fetch(getFixedOddsPda(marketId));
+      assert.equal(terms.reservedLiability.toNumber(), usdc(60));
+      const housePool = await program.account.housePool.fetch(housePoolPda);
+      assert.equal(housePool.totalReserved.sub(reservedBefore).toNumber(), usdc(60));
+    });
+
+    it("TC-FO-003: should reject a commit beyond the maximum liability", async () => {
+      const charlieBalanceBefore = (await getAccount(provider.connection, charlieTokenAccount)).amount;
+
+      try {
+        await stakeAndCommit(charlie, charlieTokenAccount, { no: {} }, usdc(50));
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "MaxLiabilityExceeded");
+      }
+
+      const charlieBalanceAfter = (await getAccount(provider.connection, charlieTokenAccount)).amount;
+      assert.equal(charlieBalanceAfter, charlieBalanceBefore);
+      const terms = await program.account.fixedOddsTerms.fetch(getFixedOddsPda(marketId));
+      assert.equal(terms.reservedLiability.toNumber(), usdc(60));
+
+      // What is left of the cap can still be taken
+      await stakeAndCommit(charlie, charlieTokenAccount, { no: {} }, usdc(40));
+    });
+  });
+
+  // ============ Lifecycle Tests (TC-LC-*) ============
+  // Resolution needs a Pyth price update, so these cover the transitions around it; the
+  // dispute window itself is unit tested in lifecycle.rs
+  describe("Lifecycle", () => {
+    const disputeMarket = async (marketId: number) => {
+      const { marketPda } = getMarketPdas(marketId);
+      await program.methods
+        .disputeMarket(new anchor.BN(marketId))
+        .accounts({ authority: admin.publicKey, config: configPda, market: marketPda })
+        .signers([admin])
+        .rpc();
+    };
+
+    const emergencyCancel = async (marketId: number) => {
+      const { marketPda } = getMarketPdas(marketId);
+      await program.methods
+        .emergencyCancelMarket(new anchor.BN(marketId), 7)
+        .accounts({ admin: admin.publicKey, config: configPda, market: marketPda, allowedCreator: null })
+        .signers([admin])
+        .rpc();
+    };
+
+    it("TC-LC-001: should move an active market to cancelled and open the claim window", async () => {
+      const marketId = await createMarket("Lifecycle cancel", 7 * 24 * 60 * 60, alice, aliceTokenAccount);
+      const before = Math.floor(Date.now() / 1000);
+
+      await emergencyCancel(marketId);
+
+      const { marketPda } = getMarketPdas(marketId);
+      const market = await program.account.market.fetch(marketPda);
+      assert.deepEqual(market.state, { cancelled: {} });
+      assert.deepEqual(market.cancelReason, { emergency: {} });
+      assert.isAbove(market.claimExpiry.toNumber(), before);
+      assert.equal(market.wasDisputed, false);
+    });
+
+    it("TC-LC-002: should reject disputing a market that was never resolved", async () => {
+      const marketId = await createMarket("Lifecycle dispute active", 7 * 24 * 60 * 60, alice, aliceTokenAccount);
+
+      try {
+        await disputeMarket(marketId);
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "IllegalStateTransition");
+      }
+
+      const { marketPda } = getMarketPdas(marketId);
+      const market = await program.account.market.fetch(marketPda);
+      assert.deepEqual(market.state, { active: {} });
+    });
+
+    it("TC-LC-003: should reject any transition out of a cancelled market", async () => {
+      const marketId = await createMarket("Lifecycle dispute cancelled", 7 * 24 * 60 * 60, alice, aliceTokenAccount);
+      await emergencyCancel(marketId);
+      const { marketPda } = getMarketPdas(marketId);
+
+      try {
+        await disputeMarket(marketId);
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "IllegalStateTransition");
+      }
+
+      try {
+        await program.methods
+          .settleDispute(new anchor.BN(marketId), true)
+          .accounts({ admin: admin.publicKey, config: configPda, market: marketPda })
+          .signers([admin])
+          .rpc();
+        assert.fail("Should have thrown error");
+      } catch (err: any) {
+        assert.include(err.message, "IllegalStateTransition");
+      }
+
+      const market = await program.account.market.fetch(marketPda);
+      assert.deepEqual(market.state, { cancelled: {} });
+      assert.equal(market.wasDisputed, false);
+    });
+  });
+
   // ============ Integration Tests (TC-IT-*) ============
   describe("Integration - Full Lifecycle", () => {
     it("TC-IT-001: complete flow YES wins", async () => {